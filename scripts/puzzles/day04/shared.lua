@@ -65,5 +65,11 @@ function Draw(ctx)
     ::continue::
   end
 
+  local metrics_y = 8
+  for name, value in next, ctx.metrics do
+    ctx.text(name .. ": " .. value, ctx.width - 240, metrics_y)
+    metrics_y = metrics_y + 16
+  end
+
   start_y = start_y - 1
 end
@@ -0,0 +1,224 @@
+use serde::Serialize;
+
+use crate::framework::{NoOpReportProgress, ReportProgress};
+use crate::load_algorithm::Solver;
+use crate::prelude::*;
+use crate::table;
+
+/// Whether allocations are counted at all. The counting allocator is only installed with the
+/// `alloc-profile` feature, and in tests, so it doesn't slow down other builds.
+const ENABLED: bool = cfg!(any(test, feature = "alloc-profile"));
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct AllocStats {
+    pub allocations: u64,
+    pub bytes_allocated: u64,
+    /// Highest amount of memory held at once, counting only memory allocated during this stage
+    pub peak_live_bytes: u64,
+}
+
+#[cfg(any(test, feature = "alloc-profile"))]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    use super::AllocStats;
+
+    #[derive(Clone, Copy)]
+    struct Counters {
+        active: bool,
+        allocations: u64,
+        bytes_allocated: u64,
+        live_bytes: i64,
+        peak_live_bytes: i64,
+    }
+
+    impl Counters {
+        const IDLE: Counters = Counters {
+            active: false,
+            allocations: 0,
+            bytes_allocated: 0,
+            live_bytes: 0,
+            peak_live_bytes: 0,
+        };
+    }
+
+    thread_local! {
+        static COUNTERS: Cell<Counters> = const { Cell::new(Counters::IDLE) };
+    }
+
+    /// Counts allocations made by threads that are inside `measure`; other threads only pay for a
+    /// thread-local lookup
+    struct CountingAllocator;
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+
+    fn record(allocated: usize, freed: usize) {
+        // try_with because the allocator is still called while thread locals are torn down
+        let _ = COUNTERS.try_with(|cell| {
+            let mut counters = cell.get();
+            if !counters.active {
+                return;
+            }
+            if allocated > 0 {
+                counters.allocations += 1;
+                counters.bytes_allocated += allocated as u64;
+            }
+            counters.live_bytes += allocated as i64 - freed as i64;
+            counters.peak_live_bytes = counters.peak_live_bytes.max(counters.live_bytes);
+            cell.set(counters);
+        });
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                record(layout.size(), 0);
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                record(layout.size(), 0);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            record(0, layout.size());
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                record(new_size, layout.size());
+            }
+            new_ptr
+        }
+    }
+
+    /// Counts the allocations made by `f` on the current thread
+    pub fn measure<T>(f: impl FnOnce() -> T) -> (T, AllocStats) {
+        COUNTERS.with(|cell| {
+            cell.set(Counters {
+                active: true,
+                ..Counters::IDLE
+            })
+        });
+        let result = f();
+        let counters = COUNTERS.with(|cell| cell.replace(Counters::IDLE));
+        (
+            result,
+            AllocStats {
+                allocations: counters.allocations,
+                bytes_allocated: counters.bytes_allocated,
+                peak_live_bytes: counters.peak_live_bytes.max(0) as u64,
+            },
+        )
+    }
+}
+
+#[cfg(any(test, feature = "alloc-profile"))]
+pub use counting::measure;
+
+/// Stands in for `measure` when no allocations are counted
+#[cfg(not(any(test, feature = "alloc-profile")))]
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, AllocStats) {
+    (f(), AllocStats::default())
+}
+
+#[derive(Debug, Serialize)]
+pub struct AllocReport {
+    pub day: String,
+    pub part: String,
+    pub answer: String,
+    pub parse: AllocStats,
+    pub solve: AllocStats,
+}
+
+pub fn profile(day: &str, part: &str, solver: &Solver, input: &str) -> Result<AllocReport> {
+    if !ENABLED {
+        bail!("Allocation profiling needs a build with `--features alloc-profile`");
+    }
+    let report_progress: Box<dyn ReportProgress> = Box::new(NoOpReportProgress);
+    let (parsed, parse) = measure(|| (solver.parse)(input));
+    let parsed = parsed?;
+    let (answer, solve) = measure(|| (solver.solve)(&parsed, &report_progress));
+    Ok(AllocReport {
+        day: day.to_string(),
+        part: part.to_string(),
+        answer: answer?,
+        parse,
+        solve,
+    })
+}
+
+pub fn print_report(report: &AllocReport) {
+    println!("{} {}: {}", report.day, report.part, report.answer);
+    let rows = [("parse", &report.parse), ("solve", &report.solve)].map(|(stage, stats)| {
+        [
+            stage.to_string(),
+            stats.allocations.to_string(),
+            stats.bytes_allocated.to_string(),
+            stats.peak_live_bytes.to_string(),
+        ]
+    });
+    table::print_table(["Stage", "Allocations", "Bytes", "Peak Live Bytes"], &rows);
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::framework::{ErasingReportProgress, FanOutReportProgress};
+    use crate::metrics::Metrics;
+    use crate::puzzles;
+    use crate::registry::Puzzle;
+
+    use super::*;
+
+    #[test]
+    fn measure_counts_allocations() {
+        let (buffer, stats) = measure(|| {
+            let _scratch = vec![0u8; 4096];
+            vec![0u8; 1024]
+        });
+        assert_eq!(buffer.len(), 1024);
+        assert_eq!(stats.allocations, 2);
+        assert_eq!(stats.bytes_allocated, 4096 + 1024);
+        assert_eq!(stats.peak_live_bytes, 4096 + 1024);
+
+        let (_, stats) = measure(|| ());
+        assert_eq!(stats.allocations, 0);
+    }
+
+    #[test]
+    fn unheard_events_dont_allocate() {
+        let input =
+            puzzles::day04::parse_input(puzzles::day04::Puzzle::samples()[0].input).unwrap();
+        let (answer, stats) = measure(|| puzzles::day04::part_two(&input, &NoOpReportProgress));
+        assert_eq!(answer.unwrap(), 4);
+        assert_eq!(stats.allocations, 0);
+
+        // the sinks behind a default `--no-window` run
+        let metrics = Metrics::default();
+        let report_progress = FanOutReportProgress {
+            sinks: vec![
+                Box::new(NoOpReportProgress),
+                Box::new(metrics.batched(Duration::MAX)),
+            ],
+        };
+        let erasing = ErasingReportProgress::new(&report_progress);
+        let (answer, stats) = measure(|| puzzles::day04::part_two(&input, &erasing));
+        assert_eq!(answer.unwrap(), 4);
+        assert_eq!(stats.allocations, 0);
+        assert!(metrics.snapshot().is_empty());
+        report_progress.flush();
+        assert_eq!(metrics.summary(), "pairs analysed=6");
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::framework::{NoOpReportProgress, ReportProgress};
+use crate::load_algorithm::Solver;
+use crate::prelude::*;
+use crate::table;
+
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub mean_ns: f64,
+    pub stddev_ns: f64,
+}
+
+impl Stats {
+    fn from_samples(samples: &[Duration]) -> Self {
+        let mut nanos = samples.iter().map(|it| it.as_nanos() as u64).collect_vec();
+        nanos.sort_unstable();
+        let median_ns = match nanos.len() {
+            0 => 0,
+            len if len % 2 == 0 => (nanos[len / 2 - 1] + nanos[len / 2]) / 2,
+            len => nanos[len / 2],
+        };
+        let count = nanos.len().max(1) as f64;
+        let mean_ns = nanos.iter().sum::<u64>() as f64 / count;
+        let variance = nanos
+            .iter()
+            .map(|&it| (it as f64 - mean_ns).powi(2))
+            .sum::<f64>()
+            / count;
+        Stats {
+            min_ns: nanos.first().copied().unwrap_or(0),
+            median_ns,
+            mean_ns,
+            stddev_ns: variance.sqrt(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub day: String,
+    pub part: String,
+    pub warmup: usize,
+    pub iterations: usize,
+    pub answer: String,
+    pub parse: Stats,
+    pub solve: Stats,
+    pub total: Stats,
+}
+
+pub fn bench(
+    day: &str,
+    part: &str,
+    solver: &Solver,
+    input: &str,
+    warmup: usize,
+    iterations: usize,
+) -> Result<BenchReport> {
+    if iterations == 0 {
+        bail!("Need at least one iteration to benchmark");
+    }
+    let report_progress: Box<dyn ReportProgress> = Box::new(NoOpReportProgress);
+
+    let mut answer = String::new();
+    for _ in 0..warmup {
+        answer = (solver.solve)(&(solver.parse)(input)?, &report_progress)?;
+    }
+
+    let mut parse_samples = Vec::with_capacity(iterations);
+    let mut solve_samples = Vec::with_capacity(iterations);
+    let mut total_samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let parsed = (solver.parse)(input)?;
+        let parsed_at = Instant::now();
+        answer = (solver.solve)(&parsed, &report_progress)?;
+        let solved_at = Instant::now();
+
+        parse_samples.push(parsed_at - start);
+        solve_samples.push(solved_at - parsed_at);
+        total_samples.push(solved_at - start);
+    }
+
+    Ok(BenchReport {
+        day: day.to_string(),
+        part: part.to_string(),
+        warmup,
+        iterations,
+        answer,
+        parse: Stats::from_samples(&parse_samples),
+        solve: Stats::from_samples(&solve_samples),
+        total: Stats::from_samples(&total_samples),
+    })
+}
+
+pub fn print_report(report: &BenchReport) {
+    println!(
+        "{} {}: {} ({} iterations after {} warmup)",
+        report.day, report.part, report.answer, report.iterations, report.warmup
+    );
+    let format_ns = |nanos: f64| format!("{:.2?}", Duration::from_nanos(nanos as u64));
+    let rows = [
+        ("parse", &report.parse),
+        ("solve", &report.solve),
+        ("total", &report.total),
+    ]
+    .map(|(stage, stats)| {
+        [
+            stage.to_string(),
+            format_ns(stats.min_ns as f64),
+            format_ns(stats.median_ns as f64),
+            format_ns(stats.mean_ns),
+            format_ns(stats.stddev_ns),
+        ]
+    });
+    table::print_table(["Stage", "Min", "Median", "Mean", "Std Dev"], &rows);
+}
+
+pub fn write_json(report: &BenchReport, path: &Path) -> Result<()> {
+    let json = serde_json::to_string_pretty(report)?;
+    fs::write(path, json + "\n")
+        .map_err(|err| anyhow!("Couldn't write {}: {err}", path.to_string_lossy()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn samples(nanos: &[u64]) -> Vec<Duration> {
+        nanos.iter().map(|&it| Duration::from_nanos(it)).collect()
+    }
+
+    #[test]
+    fn stats_from_odd_samples() {
+        let stats = Stats::from_samples(&samples(&[50, 10, 30, 20, 40]));
+        assert_eq!(stats.min_ns, 10);
+        assert_eq!(stats.median_ns, 30);
+        assert_eq!(stats.mean_ns, 30.0);
+        assert_eq!(stats.stddev_ns, 200f64.sqrt());
+    }
+
+    #[test]
+    fn stats_from_even_samples() {
+        let stats = Stats::from_samples(&samples(&[40, 10, 30, 20]));
+        assert_eq!(stats.min_ns, 10);
+        assert_eq!(stats.median_ns, 25);
+        assert_eq!(stats.mean_ns, 25.0);
+        assert_eq!(stats.stddev_ns, 125f64.sqrt());
+    }
+
+    #[test]
+    fn stats_from_no_samples() {
+        let stats = Stats::from_samples(&[]);
+        assert_eq!((stats.min_ns, stats.median_ns), (0, 0));
+        assert_eq!(stats.mean_ns, 0.0);
+    }
+}
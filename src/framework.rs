@@ -1,28 +1,994 @@
 use erased_serde::Serialize;
-use std::sync::mpsc::Sender;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::Instant;
 
-pub trait ReportProgress {
-    fn report_progress(&self, data: Box<dyn Serialize + Send>) -> ();
+use crate::metrics::Metrics;
+
+#[derive(Debug, thiserror::Error)]
+#[error("Cancelled")]
+pub struct Cancelled;
+
+/// Shared flag used to ask a running algorithm to stop
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How detailed an event is; `report_progress` reports at `Info`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+}
+
+/// A sink for progress events of type `E`. Solvers report their puzzle's own event type,
+/// while the sinks that serialize events accept any of them as `Event`.
+pub trait ReportProgress<E: ?Sized = Event> {
+    fn report_progress(&self, data: Box<E>) -> ();
+
+    fn report_progress_at(&self, _level: Level, data: Box<E>) {
+        self.report_progress(data)
+    }
+
+    /// Reports an event stamped by `StampingReportProgress`; sinks that don't keep the stamp
+    /// just report the event
+    fn report_stamped(&self, level: Level, _meta: EventMeta, data: Box<E>) {
+        self.report_progress_at(level, data)
+    }
+
+    /// Whether events at `level` go anywhere; `report!` skips building them if not
+    fn is_enabled(&self, _level: Level) -> bool {
+        true
+    }
+
+    /// Adds `delta` to a running total, for numbers that change too often to send as events
+    fn report_counter(&self, _name: &'static str, _delta: i64) {}
+
+    /// Sets a number whose latest value is all that matters, like a queue depth
+    fn report_gauge(&self, _name: &'static str, _value: f64) {}
+
+    /// Says how much of the work is done, for progress bars and time estimates
+    fn report_fraction(&self, _done: u64, _total: u64) {}
+
+    /// Sends on anything held back, like a coalesced event; called once the solver returns
+    fn flush(&self) {}
+
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+
+    /// Lets long-running solvers bail out with `?` once the run has been cancelled
+    fn check_cancelled(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Reports an event, only evaluating the expression that builds it if a sink is listening:
+/// `report!(report_progress, Level::Debug, ProgressEvent::Found { position })`.
+/// Reports at `Level::Info` if no level is given.
+macro_rules! report {
+    ($report_progress:expr, $level:expr, $event:expr $(,)?) => {{
+        let (report_progress, level) = ($report_progress, $level);
+        if report_progress.is_enabled(level) {
+            report_progress.report_progress_at(level, Box::new($event));
+        }
+    }};
+    ($report_progress:expr, $event:expr $(,)?) => {
+        $crate::framework::report!($report_progress, $crate::framework::Level::Info, $event)
+    };
+}
+pub(crate) use report;
+
+/// Passes a solver's typed events on to a sink that accepts any event
+pub struct ErasingReportProgress<'a, E> {
+    inner: &'a dyn ReportProgress,
+    event: PhantomData<fn(E)>,
+}
+
+impl<'a, E> ErasingReportProgress<'a, E> {
+    pub fn new(inner: &'a dyn ReportProgress) -> Self {
+        ErasingReportProgress {
+            inner,
+            event: PhantomData,
+        }
+    }
+}
+
+impl<E: serde::Serialize + Send + 'static> ReportProgress<E> for ErasingReportProgress<'_, E> {
+    fn report_progress(&self, data: Box<E>) {
+        self.inner.report_progress(data)
+    }
+
+    fn report_progress_at(&self, level: Level, data: Box<E>) {
+        self.inner.report_progress_at(level, data)
+    }
+
+    fn report_stamped(&self, level: Level, meta: EventMeta, data: Box<E>) {
+        self.inner.report_stamped(level, meta, data)
+    }
+
+    fn is_enabled(&self, level: Level) -> bool {
+        self.inner.is_enabled(level)
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.inner.report_counter(name, delta)
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.inner.report_gauge(name, value)
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        self.inner.report_fraction(done, total)
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+}
+
+/// Collects events, so tests can assert on exactly what a solver reported
+impl<E: Send> ReportProgress<E> for Mutex<Vec<E>> {
+    fn report_progress(&self, data: Box<E>) {
+        self.lock().unwrap().push(*data)
+    }
 }
 
-impl ReportProgress for Box<dyn ReportProgress> {
-    fn report_progress(&self, data: Box<dyn Serialize + Send>) -> () {
+impl ReportProgress for Box<dyn ReportProgress + Send> {
+    fn report_progress(&self, data: Box<dyn Serialize + Send>) {
         self.as_ref().report_progress(data)
     }
+
+    fn report_progress_at(&self, level: Level, data: Box<dyn Serialize + Send>) {
+        self.as_ref().report_progress_at(level, data)
+    }
+
+    fn report_stamped(&self, level: Level, meta: EventMeta, data: Box<dyn Serialize + Send>) {
+        self.as_ref().report_stamped(level, meta, data)
+    }
+
+    fn is_enabled(&self, level: Level) -> bool {
+        self.as_ref().is_enabled(level)
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.as_ref().report_counter(name, delta)
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.as_ref().report_gauge(name, value)
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        self.as_ref().report_fraction(done, total)
+    }
+
+    fn flush(&self) {
+        self.as_ref().flush()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.as_ref().is_cancelled()
+    }
 }
 
+/// Adapters for chaining sinks, e.g. `sink.filter(..).map(..)`
+pub trait ReportProgressExt: ReportProgress + Send + Sized + 'static {
+    fn filter(
+        self,
+        predicate: impl Fn(Level, &Event) -> bool + Send + 'static,
+    ) -> FilterReportProgress {
+        FilterReportProgress {
+            inner: Box::new(self),
+            predicate: Box::new(predicate),
+        }
+    }
+
+    // nothing in the binary rewrites events at the moment
+    #[allow(dead_code)]
+    fn map(
+        self,
+        transform: impl Fn(Box<Event>) -> Box<Event> + Send + 'static,
+    ) -> MapReportProgress {
+        MapReportProgress {
+            inner: Box::new(self),
+            transform: Box::new(transform),
+        }
+    }
+}
+
+impl<T: ReportProgress + Send + 'static> ReportProgressExt for T {}
+
+/// What `AsyncReportProgress` does when the solver outpaces the visualizer and the queue fills up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueuePolicy {
+    /// Wait for room in the queue
+    Block,
+    /// Drop events while the queue is full, except every Nth, which waits for room
+    Sample { every: usize },
+    /// Keep only the latest event while the queue is full
+    Coalesce,
+}
+
+impl FromStr for QueuePolicy {
+    type Err = String;
+
+    /// Parses `block`, `sample:<n>` or `coalesce`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "block" => Ok(QueuePolicy::Block),
+            None if s == "coalesce" => Ok(QueuePolicy::Coalesce),
+            Some(("sample", every)) => match every.parse() {
+                Ok(every) if every > 0 => Ok(QueuePolicy::Sample { every }),
+                _ => Err(format!("Invalid sample rate {every:?}")),
+            },
+            _ => Err(format!(
+                "Unknown queue policy {s:?}; expected block, sample:<n> or coalesce"
+            )),
+        }
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    overflowed: usize,
+    pending: Option<Envelope>,
+}
+
+/// What became of an event offered to the queue
+enum Offered {
+    Handled,
+    /// The receiver is gone
+    Closed,
+    /// The queue is full and the policy says to wait for room
+    MustBlock(Envelope),
+}
+
+/// Events waiting in the queue to the visualizer, and events that never made it in
+#[derive(Clone, Debug, Default)]
+pub struct QueueCounts {
+    /// Sent but not yet received; channels don't report their length themselves
+    pub queued: Arc<AtomicUsize>,
+    pub dropped: Arc<AtomicUsize>,
+}
+
+impl QueueCounts {
+    /// Counts the event as queued before sending it, so the receiver never sees it uncounted
+    fn counting_send<T, E>(&self, send: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        let result = send();
+        if result.is_err() {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Returns false if the receiver is gone
+    pub fn send(&self, sender: &Sender<Envelope>, envelope: Envelope) -> bool {
+        self.counting_send(|| sender.send(envelope)).is_ok()
+    }
+
+    /// Call for each envelope taken off the queue
+    pub fn received(&self) {
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Sends events to the visualizer over a bounded queue
 pub struct AsyncReportProgress {
-    pub sender: Sender<Box<Event>>,
+    sender: SyncSender<Envelope>,
+    policy: QueuePolicy,
+    state: Mutex<QueueState>,
+    /// Set once the receiver is gone, e.g. because the window was closed. Kept out of `state`
+    /// so checking it never waits on a sender blocked on a full queue.
+    closed: AtomicBool,
+    pub counts: QueueCounts,
+    /// Counters and gauges skip the queue; the window reads them once a frame
+    pub metrics: Metrics,
 }
+
+impl AsyncReportProgress {
+    pub fn new(policy: QueuePolicy, capacity: usize) -> (Self, Receiver<Envelope>) {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let report_progress = AsyncReportProgress {
+            sender,
+            policy,
+            state: Mutex::new(QueueState::default()),
+            closed: AtomicBool::new(false),
+            counts: QueueCounts::default(),
+            metrics: Metrics::default(),
+        };
+        (report_progress, receiver)
+    }
+
+    fn drop_event(&self) {
+        self.counts.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Call without holding `state`, as this waits for room in the queue
+    fn send_blocking(&self, event: Envelope) {
+        if self
+            .counts
+            .counting_send(|| self.sender.send(event))
+            .is_err()
+        {
+            self.closed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Sends the event if there's room, otherwise applies the policy short of blocking
+    fn offer(&self, state: &mut QueueState, event: Envelope) -> Offered {
+        let event = match self.counts.counting_send(|| self.sender.try_send(event)) {
+            Ok(()) => return Offered::Handled,
+            Err(TrySendError::Disconnected(_)) => return Offered::Closed,
+            Err(TrySendError::Full(event)) => event,
+        };
+        match self.policy {
+            QueuePolicy::Block => Offered::MustBlock(event),
+            QueuePolicy::Sample { every } => {
+                state.overflowed += 1;
+                if state.overflowed.is_multiple_of(every) {
+                    Offered::MustBlock(event)
+                } else {
+                    self.drop_event();
+                    Offered::Handled
+                }
+            }
+            QueuePolicy::Coalesce => {
+                if state.pending.replace(event).is_some() {
+                    self.drop_event();
+                }
+                Offered::Handled
+            }
+        }
+    }
+}
+
 impl ReportProgress for AsyncReportProgress {
-    fn report_progress(&self, data: Box<dyn Serialize + Send>) -> () {
-        self.sender.send(Box::new(data)).unwrap();
+    /// Events are meant to come through `StampingReportProgress`; ones that don't are sent
+    /// with an empty stamp
+    fn report_progress(&self, data: Box<dyn Serialize + Send>) {
+        self.report_stamped(Level::Info, EventMeta::default(), data)
+    }
+
+    fn report_stamped(&self, _level: Level, meta: EventMeta, data: Box<dyn Serialize + Send>) {
+        if self.is_cancelled() {
+            return;
+        }
+        let envelope = Envelope { meta, event: data };
+        let offered = {
+            let mut state = self.state.lock().unwrap();
+            // only coalescing holds events back, and it never blocks
+            if let Some(pending) = state.pending.take() {
+                if let Offered::Closed = self.offer(&mut state, pending) {
+                    self.closed.store(true, Ordering::Relaxed);
+                    return;
+                }
+            }
+            self.offer(&mut state, envelope)
+        };
+        match offered {
+            Offered::Handled => {}
+            Offered::Closed => self.closed.store(true, Ordering::Relaxed),
+            Offered::MustBlock(event) => self.send_blocking(event),
+        }
+    }
+
+    fn is_enabled(&self, _level: Level) -> bool {
+        !self.is_cancelled()
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.metrics.report_counter(name, delta)
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.metrics.report_gauge(name, value)
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        self.metrics.report_fraction(done, total)
+    }
+
+    fn flush(&self) {
+        let pending = self.state.lock().unwrap().pending.take();
+        if let Some(pending) = pending {
+            if !self.is_cancelled() {
+                self.send_blocking(pending);
+            }
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for AsyncReportProgress {
+    fn drop(&mut self) {
+        self.flush()
     }
 }
 
 pub struct NoOpReportProgress;
-impl ReportProgress for NoOpReportProgress {
-    fn report_progress(&self, _data: Box<dyn Serialize + Send>) -> () {}
+impl<E: ?Sized> ReportProgress<E> for NoOpReportProgress {
+    fn report_progress(&self, _data: Box<E>) {}
+
+    fn is_enabled(&self, _level: Level) -> bool {
+        false
+    }
+}
+
+/// Drops events once the token is cancelled, and exposes it to the solver
+pub struct CancellableReportProgress {
+    pub inner: Box<dyn ReportProgress + Send>,
+    pub cancellation: CancellationToken,
+}
+impl ReportProgress for CancellableReportProgress {
+    fn report_progress(&self, data: Box<dyn Serialize + Send>) {
+        self.report_progress_at(Level::Info, data)
+    }
+
+    fn report_progress_at(&self, level: Level, data: Box<dyn Serialize + Send>) {
+        if !self.is_cancelled() {
+            self.inner.report_progress_at(level, data)
+        }
+    }
+
+    fn report_stamped(&self, level: Level, meta: EventMeta, data: Box<dyn Serialize + Send>) {
+        if !self.is_cancelled() {
+            self.inner.report_stamped(level, meta, data)
+        }
+    }
+
+    fn is_enabled(&self, level: Level) -> bool {
+        !self.is_cancelled() && self.inner.is_enabled(level)
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.inner.report_counter(name, delta)
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.inner.report_gauge(name, value)
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        self.inner.report_fraction(done, total)
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled() || self.inner.is_cancelled()
+    }
+}
+
+/// Drops events below a minimum level before they're sent anywhere
+pub struct LevelFilterReportProgress {
+    pub inner: Box<dyn ReportProgress + Send>,
+    pub min_level: Level,
+}
+impl ReportProgress for LevelFilterReportProgress {
+    fn report_progress(&self, data: Box<dyn Serialize + Send>) {
+        self.report_progress_at(Level::Info, data)
+    }
+
+    fn report_progress_at(&self, level: Level, data: Box<dyn Serialize + Send>) {
+        if level >= self.min_level {
+            self.inner.report_progress_at(level, data)
+        }
+    }
+
+    fn report_stamped(&self, level: Level, meta: EventMeta, data: Box<dyn Serialize + Send>) {
+        if level >= self.min_level {
+            self.inner.report_stamped(level, meta, data)
+        }
+    }
+
+    fn is_enabled(&self, level: Level) -> bool {
+        level >= self.min_level && self.inner.is_enabled(level)
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.inner.report_counter(name, delta)
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.inner.report_gauge(name, value)
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        self.inner.report_fraction(done, total)
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+}
+
+/// Only passes on the events `predicate` accepts
+pub struct FilterReportProgress {
+    inner: Box<dyn ReportProgress + Send>,
+    predicate: Box<EventPredicate>,
+}
+impl ReportProgress for FilterReportProgress {
+    fn report_progress(&self, data: Box<dyn Serialize + Send>) {
+        self.report_progress_at(Level::Info, data)
+    }
+
+    fn report_progress_at(&self, level: Level, data: Box<dyn Serialize + Send>) {
+        if (self.predicate)(level, data.as_ref()) {
+            self.inner.report_progress_at(level, data)
+        }
+    }
+
+    fn report_stamped(&self, level: Level, meta: EventMeta, data: Box<dyn Serialize + Send>) {
+        if (self.predicate)(level, data.as_ref()) {
+            self.inner.report_stamped(level, meta, data)
+        }
+    }
+
+    fn is_enabled(&self, level: Level) -> bool {
+        self.inner.is_enabled(level)
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.inner.report_counter(name, delta)
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.inner.report_gauge(name, value)
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        self.inner.report_fraction(done, total)
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+}
+
+/// Replaces each event with `transform(event)` before passing it on
+#[allow(dead_code)]
+pub struct MapReportProgress {
+    inner: Box<dyn ReportProgress + Send>,
+    transform: Box<EventTransform>,
+}
+impl ReportProgress for MapReportProgress {
+    fn report_progress(&self, data: Box<dyn Serialize + Send>) {
+        self.report_progress_at(Level::Info, data)
+    }
+
+    fn report_progress_at(&self, level: Level, data: Box<dyn Serialize + Send>) {
+        self.inner.report_progress_at(level, (self.transform)(data))
+    }
+
+    fn report_stamped(&self, level: Level, meta: EventMeta, data: Box<dyn Serialize + Send>) {
+        self.inner
+            .report_stamped(level, meta, (self.transform)(data))
+    }
+
+    fn is_enabled(&self, level: Level) -> bool {
+        self.inner.is_enabled(level)
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.inner.report_counter(name, delta)
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.inner.report_gauge(name, value)
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        self.inner.report_fraction(done, total)
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+}
+
+/// Counts every event the solver reports, including ones `inner` isn't listening for, so it
+/// makes `report!` build them all
+pub struct CountingReportProgress {
+    pub inner: Box<dyn ReportProgress + Send>,
+    pub count: Arc<AtomicUsize>,
+}
+impl ReportProgress for CountingReportProgress {
+    fn report_progress(&self, data: Box<dyn Serialize + Send>) {
+        self.report_progress_at(Level::Info, data)
+    }
+
+    fn report_progress_at(&self, level: Level, data: Box<dyn Serialize + Send>) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        if self.inner.is_enabled(level) {
+            self.inner.report_progress_at(level, data)
+        }
+    }
+
+    fn report_stamped(&self, level: Level, meta: EventMeta, data: Box<dyn Serialize + Send>) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        if self.inner.is_enabled(level) {
+            self.inner.report_stamped(level, meta, data)
+        }
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.inner.report_counter(name, delta)
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.inner.report_gauge(name, value)
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        self.inner.report_fraction(done, total)
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+}
+
+/// An event shared between several sinks, each of which serializes the original value. The
+/// mutex only makes it `Sync`; it's locked just while a sink serializes the event.
+#[derive(Clone)]
+struct SharedEvent(Arc<Mutex<Box<Event>>>);
+
+impl serde::Serialize for SharedEvent {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.lock().unwrap().serialize(serializer)
+    }
+}
+
+/// Passes every event on to each of `sinks`, and is cancelled once any of them is
+pub struct FanOutReportProgress {
+    pub sinks: Vec<Box<dyn ReportProgress + Send>>,
+}
+impl FanOutReportProgress {
+    fn fan_out(
+        &self,
+        level: Level,
+        data: Box<Event>,
+        report: impl Fn(&(dyn ReportProgress + Send), Box<Event>),
+    ) {
+        // sinks that only track metrics don't need the event shared with them
+        let mut enabled = self.sinks.iter().filter(|it| it.is_enabled(level));
+        match (enabled.next(), enabled.next()) {
+            (None, _) => {}
+            (Some(sink), None) => report(sink.as_ref(), data),
+            (Some(_), Some(_)) => {
+                let shared = SharedEvent(Arc::new(Mutex::new(data)));
+                for sink in self.sinks.iter().filter(|it| it.is_enabled(level)) {
+                    report(sink.as_ref(), Box::new(shared.clone()));
+                }
+            }
+        }
+    }
+}
+impl ReportProgress for FanOutReportProgress {
+    fn report_progress(&self, data: Box<dyn Serialize + Send>) {
+        self.report_progress_at(Level::Info, data)
+    }
+
+    fn report_progress_at(&self, level: Level, data: Box<dyn Serialize + Send>) {
+        self.fan_out(level, data, |sink, data| {
+            sink.report_progress_at(level, data)
+        })
+    }
+
+    fn report_stamped(&self, level: Level, meta: EventMeta, data: Box<dyn Serialize + Send>) {
+        self.fan_out(level, data, |sink, data| {
+            sink.report_stamped(level, meta.clone(), data)
+        })
+    }
+
+    fn is_enabled(&self, level: Level) -> bool {
+        self.sinks.iter().any(|it| it.is_enabled(level))
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        for sink in self.sinks.iter() {
+            sink.report_counter(name, delta);
+        }
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        for sink in self.sinks.iter() {
+            sink.report_gauge(name, value);
+        }
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        for sink in self.sinks.iter() {
+            sink.report_fraction(done, total);
+        }
+    }
+
+    fn flush(&self) {
+        for sink in self.sinks.iter() {
+            sink.flush();
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.sinks.iter().any(|it| it.is_cancelled())
+    }
 }
 
 pub type Event = dyn Serialize + Send;
+pub type EventPredicate = dyn Fn(Level, &Event) -> bool + Send;
+#[allow(dead_code)]
+pub type EventTransform = dyn Fn(Box<Event>) -> Box<Event> + Send;
+
+/// When and where an event was reported
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct EventMeta {
+    /// Position in the stream, counting events that were later dropped
+    pub seq: u64,
+    /// Milliseconds since the solver started
+    pub elapsed_ms: f64,
+    /// The reporting thread's name, or its id if it doesn't have one
+    pub thread: String,
+}
+
+pub struct Envelope {
+    pub meta: EventMeta,
+    pub event: Box<Event>,
+}
+
+#[derive(Debug, Default)]
+struct StamperState {
+    start: OnceLock<Instant>,
+    next_seq: AtomicU64,
+}
+
+/// Hands out sequence numbers and timestamps for a run's events. Clones share the count and
+/// the clock, which runs from `start`.
+#[derive(Clone, Debug, Default)]
+pub struct Stamper(Arc<StamperState>);
+
+impl Stamper {
+    /// Starts the clock, if it isn't running already; called as the solver starts
+    pub fn start(&self) {
+        self.0.start.get_or_init(Instant::now);
+    }
+
+    pub fn elapsed_ms(&self) -> f64 {
+        match self.0.start.get() {
+            Some(start) => start.elapsed().as_secs_f64() * 1000.0,
+            None => 0.0,
+        }
+    }
+
+    pub fn stamp(&self) -> EventMeta {
+        let current = thread::current();
+        EventMeta {
+            seq: self.0.next_seq.fetch_add(1, Ordering::Relaxed),
+            elapsed_ms: self.elapsed_ms(),
+            thread: match current.name() {
+                Some(name) => name.to_string(),
+                None => format!("{:?}", current.id()),
+            },
+        }
+    }
+}
+
+/// Stamps each event once, before it fans out, so every sink sees the same sequence number
+/// and time
+pub struct StampingReportProgress {
+    pub inner: Box<dyn ReportProgress + Send>,
+    pub stamper: Stamper,
+}
+impl ReportProgress for StampingReportProgress {
+    fn report_progress(&self, data: Box<dyn Serialize + Send>) {
+        self.report_progress_at(Level::Info, data)
+    }
+
+    fn report_progress_at(&self, level: Level, data: Box<dyn Serialize + Send>) {
+        self.inner.report_stamped(level, self.stamper.stamp(), data)
+    }
+
+    fn is_enabled(&self, level: Level) -> bool {
+        self.inner.is_enabled(level)
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.inner.report_counter(name, delta)
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.inner.report_gauge(name, value)
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        self.inner.report_fraction(done, total)
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn received(receiver: &Receiver<Envelope>) -> Vec<serde_json::Value> {
+        receiver
+            .try_iter()
+            .map(|it| serde_json::to_value(it.event).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parse_queue_policy() {
+        assert_eq!("block".parse(), Ok(QueuePolicy::Block));
+        assert_eq!("sample:10".parse(), Ok(QueuePolicy::Sample { every: 10 }));
+        assert_eq!("coalesce".parse(), Ok(QueuePolicy::Coalesce));
+        assert!("sample:0".parse::<QueuePolicy>().is_err());
+        assert!("drop".parse::<QueuePolicy>().is_err());
+    }
+
+    #[test]
+    fn coalesce_keeps_latest() {
+        let (report_progress, receiver) = AsyncReportProgress::new(QueuePolicy::Coalesce, 2);
+        for i in 0..6 {
+            report_progress.report_progress(Box::new(i));
+        }
+        assert_eq!(report_progress.counts.queued(), 2);
+        assert_eq!(received(&receiver), [0, 1]);
+        assert_eq!(report_progress.counts.dropped(), 3);
+        report_progress.flush();
+        assert_eq!(received(&receiver), [5]);
+        drop(report_progress);
+        assert!(received(&receiver).is_empty());
+    }
+
+    #[test]
+    fn sample_sends_every_nth_overflow() {
+        let (async_sink, receiver) = AsyncReportProgress::new(QueuePolicy::Sample { every: 2 }, 1);
+        let counts = async_sink.counts.clone();
+        let report_progress = StampingReportProgress {
+            inner: Box::new(async_sink),
+            stamper: Stamper::default(),
+        };
+        report_progress.report_progress(Box::new(0));
+        report_progress.report_progress(Box::new(1));
+        assert_eq!(received(&receiver), [0]);
+        report_progress.report_progress(Box::new(2));
+        let sender = std::thread::spawn(move || {
+            report_progress.report_progress(Box::new(3));
+            counts.dropped()
+        });
+        let next = receiver.recv().unwrap();
+        assert_eq!(serde_json::to_value(next.event).unwrap(), 2);
+        assert_eq!(next.meta.seq, 2);
+        assert_eq!(sender.join().unwrap(), 1);
+        assert_eq!(received(&receiver), [3]);
+    }
+
+    #[test]
+    fn level_filter() {
+        let (sender, receiver) = AsyncReportProgress::new(QueuePolicy::Block, 10);
+        let report_progress = LevelFilterReportProgress {
+            inner: Box::new(sender),
+            min_level: Level::Debug,
+        };
+        report_progress.report_progress_at(Level::Trace, Box::new(0));
+        report_progress.report_progress_at(Level::Debug, Box::new(1));
+        report_progress.report_progress(Box::new(2));
+        assert_eq!(received(&receiver), [1, 2]);
+    }
+
+    #[test]
+    fn fan_out_filter_map() {
+        let (first, first_receiver) = AsyncReportProgress::new(QueuePolicy::Block, 10);
+        let (second, second_receiver) = AsyncReportProgress::new(QueuePolicy::Block, 10);
+        let report_progress = FanOutReportProgress {
+            sinks: vec![
+                Box::new(first),
+                Box::new(second.filter(|level, _| level >= Level::Debug)),
+            ],
+        }
+        .map(|event| Box::new(vec![event]));
+        report_progress.report_progress_at(Level::Trace, Box::new(1));
+        report_progress.report_progress(Box::new(2));
+        assert_eq!(
+            received(&first_receiver),
+            [serde_json::json!([1]), serde_json::json!([2])]
+        );
+        assert_eq!(received(&second_receiver), [serde_json::json!([2])]);
+        drop(second_receiver);
+        assert!(!report_progress.is_cancelled());
+        report_progress.report_progress(Box::new(3));
+        assert!(report_progress.is_cancelled());
+    }
+
+    #[test]
+    fn closed_receiver_cancels() {
+        let (report_progress, receiver) = AsyncReportProgress::new(QueuePolicy::Block, 1);
+        drop(receiver);
+        report_progress.report_progress(Box::new(0));
+        assert!(report_progress.is_cancelled());
+    }
+
+    #[test]
+    fn blocked_sender_doesnt_hold_up_cancellation_checks() {
+        let (report_progress, receiver) = AsyncReportProgress::new(QueuePolicy::Block, 1);
+        let report_progress = Arc::new(report_progress);
+        report_progress.report_progress(Box::new(0));
+        let blocked = {
+            let report_progress = report_progress.clone();
+            thread::spawn(move || report_progress.report_progress(Box::new(1)))
+        };
+        // counted as queued just before it waits for room
+        while report_progress.counts.queued() < 2 {
+            thread::yield_now();
+        }
+        assert!(report_progress.is_enabled(Level::Info));
+        assert!(!report_progress.is_cancelled());
+        assert_eq!(
+            serde_json::to_value(receiver.recv().unwrap().event).unwrap(),
+            0
+        );
+        blocked.join().unwrap();
+        assert_eq!(received(&receiver), [1]);
+    }
+}
@@ -0,0 +1,32 @@
+use crate::load_algorithm;
+use crate::prelude::*;
+use crate::puzzles;
+use crate::table;
+
+pub fn print_list() {
+    let yes_no = |value: bool| if value { "yes" } else { "no" }.to_string();
+    let rows = puzzles::registry()
+        .iter()
+        .flat_map(|puzzle| {
+            puzzle.parts.iter().map(|part| {
+                let script_path = load_algorithm::script_path(puzzle.day, part.name);
+                let input_path = load_algorithm::named_input_path(puzzle.day);
+                [
+                    puzzle.day.to_string(),
+                    part.name.to_string(),
+                    puzzle.title.to_string(),
+                    yes_no(script_path.exists()),
+                    match (part.emits_progress, puzzle.events) {
+                        (true, Some(events)) => events.to_string(),
+                        (emits_progress, _) => yes_no(emits_progress),
+                    },
+                    yes_no(input_path.exists()),
+                ]
+            })
+        })
+        .collect_vec();
+    table::print_table(
+        ["Day", "Part", "Title", "Script", "Progress", "Input"],
+        &rows,
+    );
+}
@@ -1,50 +1,134 @@
-use std::path::Path;
+use std::any::Any;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 
 use crate::framework::ReportProgress;
 use crate::lua::draw_runtime::DrawRuntime;
 use crate::prelude::*;
-use crate::puzzles::*;
+use crate::registry::{self, RegisteredPuzzle};
+
+pub type ThreadFunc = Box<dyn (Fn(&str, &Box<dyn ReportProgress>) -> Result<String>) + Send>;
+pub type Parsed = Box<dyn Any + Send>;
+pub type ParseFunc = Box<dyn (Fn(&str) -> Result<Parsed>) + Send>;
+pub type SolveFunc = Box<dyn (Fn(&Parsed, &Box<dyn ReportProgress>) -> Result<String>) + Send>;
+
+/// A puzzle part split into its parse and solve stages, so they can be measured separately
+pub struct Solver {
+    pub parse: ParseFunc,
+    pub solve: SolveFunc,
+}
+
+impl Solver {
+    pub fn new<T: Send + 'static, R: ToString + 'static>(
+        parse: fn(&str) -> Result<T>,
+        solve: fn(&T, &Box<dyn ReportProgress>) -> Result<R>,
+    ) -> Self {
+        Solver {
+            parse: Box::new(move |input| Ok(Box::new(parse(input)?) as Parsed)),
+            solve: Box::new(move |parsed, report_progress| {
+                let parsed = parsed
+                    .downcast_ref::<T>()
+                    .ok_or_else(|| anyhow!("Parsed input doesn't match this solver"))?;
+                solve(parsed, report_progress).map(|it| it.to_string())
+            }),
+        }
+    }
+
+    pub fn into_thread_func(self) -> ThreadFunc {
+        Box::new(move |input, report_progress| {
+            let parsed = (self.parse)(input)?;
+            (self.solve)(&parsed, report_progress)
+        })
+    }
+}
 
-pub type ThreadFunc = Box<dyn (Fn(&Box<dyn ReportProgress>) -> Result<String>) + Send>;
 pub struct Algorithm {
+    pub day: &'static str,
+    pub part: &'static str,
     pub draw_runtime: DrawRuntime,
     pub thread_func: ThreadFunc,
+    pub input: String,
+    pub emits_progress: bool,
+    /// Known answer for the input, if it's one of the puzzle's samples
+    pub expected: Option<&'static str>,
 }
 
-pub fn load(day: &str, part: &str) -> Result<Algorithm> {
-    let thread_func: ThreadFunc = match (day, part) {
-        ("test_algo", "part_one") => Box::new(|_| Ok(test_algo::part_one().to_string())),
-        ("test_algo", "part_two") => Box::new(|rp| Ok(test_algo::part_two(rp).to_string())),
-        ("day01", "part_one") => Box::new(|_| day01::part_one().map(|it| it.to_string())),
-        ("day01", "part_two") => Box::new(|_| day01::part_two().map(|it| it.to_string())),
-        ("day02", "part_one") => Box::new(|_| day02::part_one().map(|it| it.to_string())),
-        ("day02", "part_two") => Box::new(|_| day02::part_two().map(|it| it.to_string())),
-        ("day03", "part_one") => Box::new(|_| day03::part_one().map(|it| it.to_string())),
-        ("day03", "part_two") => Box::new(|_| day03::part_two().map(|it| it.to_string())),
-        ("day04", "part_one") => {
-            Box::new(|progress| day04::part_one(progress).map(|it| it.to_string()))
-        }
-        ("day04", "part_two") => {
-            Box::new(|progress| day04::part_two(progress).map(|it| it.to_string()))
-        }
-        ("day05", "part_one") => Box::new(|_| day05::part_one()),
-        ("day05", "part_two") => Box::new(|_| day05::part_two()),
-        ("day06", "part_one") => Box::new(|_| day06::part_one().map(|it| it.to_string())),
-        ("day06", "part_two") => Box::new(|_| day06::part_two().map(|it| it.to_string())),
-        ("day07", "part_one") => Box::new(|_| day07::part_one().map(|it| it.to_string())),
-        ("day07", "part_two") => Box::new(|_| day07::part_two().map(|it| it.to_string())),
-        (_, _) => bail!("Couldn't find {day} {part}"),
-    };
-
-    let file_path = Path::new("scripts")
+pub enum InputSource {
+    /// `inputs/<day>.txt`
+    Default,
+    File(PathBuf),
+    /// One of the puzzle's samples; the first one if no name is given
+    Sample(Option<String>),
+}
+
+/// `inputs/<name>.txt`; a day's own input is named after the day
+pub fn named_input_path(name: &str) -> PathBuf {
+    Path::new("inputs").join(format!("{name}.txt"))
+}
+
+/// Reads the input from stdin if the path is `-`
+pub fn load_input(day: &str, input_path: Option<&Path>) -> Result<String> {
+    if input_path == Some(Path::new("-")) {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|err| anyhow!("Couldn't read puzzle input for {day} from stdin: {err}"))?;
+        return Ok(input);
+    }
+    let input_path = input_path
+        .map(|it| it.to_path_buf())
+        .unwrap_or_else(|| named_input_path(day));
+    fs::read_to_string(&input_path).map_err(|err| {
+        anyhow!(
+            "Couldn't read puzzle input for {day} at {}: {err}",
+            input_path.to_string_lossy()
+        )
+    })
+}
+
+pub fn script_path(day: &str, part: &str) -> PathBuf {
+    Path::new("scripts")
         .join("puzzles")
         .join(day)
-        .join(format!("{part}.lua"));
+        .join(format!("{part}.lua"))
+}
+
+pub fn load_solver(day: &str, part: &str) -> Result<Solver> {
+    registry::lookup(day, part).map(|(_, part)| part.solver)
+}
+
+/// Returns the input along with its known answer, if it's one of the puzzle's samples
+pub fn resolve_input(
+    puzzle: &RegisteredPuzzle,
+    part: &str,
+    input_source: &InputSource,
+) -> Result<(String, Option<&'static str>)> {
+    match input_source {
+        InputSource::Default => Ok((load_input(puzzle.day, None)?, None)),
+        InputSource::File(path) => Ok((load_input(puzzle.day, Some(path))?, None)),
+        InputSource::Sample(name) => {
+            let sample = puzzle.sample(name.as_deref())?;
+            Ok((sample.input.to_string(), sample.answer(part)))
+        }
+    }
+}
+
+pub fn load(day: &str, part: &str, input_source: &InputSource) -> Result<Algorithm> {
+    let (puzzle, part) = registry::lookup(day, part)?;
+    let emits_progress = part.emits_progress;
+    let thread_func = part.solver.into_thread_func();
+    let (input, expected) = resolve_input(&puzzle, part.name, input_source)?;
 
-    let draw_runtime = DrawRuntime::new(&file_path);
+    let draw_runtime = DrawRuntime::new(&script_path(puzzle.day, part.name));
 
     Ok(Algorithm {
+        day: puzzle.day,
+        part: part.name,
         draw_runtime,
         thread_func,
+        input,
+        emits_progress,
+        expected,
     })
 }
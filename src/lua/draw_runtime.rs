@@ -1,6 +1,6 @@
 use crate::{
     draw_utils::{self, str_to_color},
-    framework::Event,
+    framework::{Event, EventMeta},
     prelude::*,
 };
 use ggez::{
@@ -10,6 +10,7 @@ use ggez::{
 use rlua::prelude::*;
 use std::{
     cell::RefCell,
+    collections::BTreeMap,
     fmt::Display,
     fs,
     path::{Path, PathBuf},
@@ -49,25 +50,28 @@ pub struct DrawRuntime {
 
 pub struct DrawRuntimeData {
     lua: Lua,
+    /// Event variants listed in the script's `WantedEvents` global, if it has one
+    wanted_events: Option<Vec<String>>,
 }
 
 impl DrawRuntime {
     pub fn new(module_path: &Path) -> Self {
         let lua = Lua::new();
         let module_path = module_path.to_path_buf();
-        match lua.context(|ctx| {
+        let wanted_events = match lua.context(|ctx| {
             ctx.load(include_str!("./runtime.lua"))
                 .set_name("aoc2022:runtime.lua")?
                 .exec()?;
             let source = fs::read(&module_path)
                 .map_err(|_| anyhow!("Can't find a file at {}", module_path.to_string_lossy()))?
-                .pipe(|it| String::from_utf8(it))?;
+                .pipe(String::from_utf8)?;
             ctx.load(&source)
                 .set_name(module_path.to_str().unwrap())?
                 .exec()?;
-            Ok(())
+            let wanted_events: Option<Vec<String>> = ctx.globals().get("WantedEvents")?;
+            Ok(wanted_events)
         }) {
-            Ok(_) => (),
+            Ok(wanted_events) => wanted_events,
             Err(err) => {
                 return DrawRuntime {
                     initial_module_path: module_path,
@@ -77,14 +81,14 @@ impl DrawRuntime {
         };
 
         DrawRuntime {
-            result: Ok(DrawRuntimeData { lua }),
+            result: Ok(DrawRuntimeData { lua, wanted_events }),
             initial_module_path: module_path,
         }
     }
 
     pub fn get_loaded_modules(&self) -> Result<Vec<PathBuf>> {
         match &self.result {
-            Ok(DrawRuntimeData { lua }) => {
+            Ok(DrawRuntimeData { lua, .. }) => {
                 let mut additional_packages = lua.context(|ctx| {
                     let package = ctx.globals().get::<_, LuaTable>("package")?;
                     let loaded = package
@@ -115,7 +119,7 @@ impl DrawRuntime {
                         })
                         .collect::<Vec<_>>();
 
-                    anyhow::Ok(loaded.into_iter().filter_map(|it| it).collect_vec())
+                    anyhow::Ok(loaded.into_iter().flatten().collect_vec())
                 })?;
 
                 additional_packages.push(self.initial_module_path.clone());
@@ -125,7 +129,12 @@ impl DrawRuntime {
         }
     }
 
-    pub fn draw(&mut self, gfx_ctx: &ggez::Context, canvas: &mut Canvas) -> Result<()> {
+    pub fn draw(
+        &mut self,
+        gfx_ctx: &ggez::Context,
+        canvas: &mut Canvas,
+        metrics: &BTreeMap<String, f64>,
+    ) -> Result<()> {
         let DrawRuntimeData { lua, .. } = match &mut self.result {
             Ok(it) => it,
             Err(err) => return Err(anyhow!(err.0.clone())),
@@ -148,7 +157,7 @@ impl DrawRuntime {
                                 graphics::Rect::new(0.0, 0.0, width, height),
                                 color,
                             )
-                            .map_err(|err| LuaError::external(err))?;
+                            .map_err(LuaError::external)?;
                             canvas_cell.borrow_mut().draw(&shape, Vec2::new(x, y));
                             Ok(())
                         },
@@ -175,12 +184,14 @@ impl DrawRuntime {
                                 graphics::Rect::new(0.0, 0.0, width, height),
                                 color,
                             )
-                            .map_err(|err| LuaError::external(err))?;
+                            .map_err(LuaError::external)?;
                             canvas_cell.borrow_mut().draw(&shape, Vec2::new(x, y));
                             Ok(())
                         },
                     )?,
                 )?;
+                // not wired up to TextOpts yet
+                #[allow(dead_code)]
                 #[derive(Debug)]
                 enum VAlign {
                     Top,
@@ -208,6 +219,7 @@ impl DrawRuntime {
                         .pipe(Ok)
                     }
                 }
+                #[allow(dead_code)]
                 #[derive(Debug)]
                 enum HAlign {
                     Left,
@@ -267,7 +279,7 @@ impl DrawRuntime {
                             let mut text = graphics::Text::new(&text);
                             text.set_scale(opts.size.unwrap_or(16.0));
                             canvas_cell.borrow_mut().draw(
-                                &mut text,
+                                &text,
                                 DrawParam::default()
                                     .dest(Vec2::new(x, y))
                                     .color(opts.color.unwrap_or(draw_utils::BLACK)),
@@ -279,16 +291,34 @@ impl DrawRuntime {
                 let size = gfx_ctx.gfx.drawable_size();
                 draw_ctx.set("width", size.0)?;
                 draw_ctx.set("height", size.1)?;
+                draw_ctx.set(
+                    "metrics",
+                    ctx.create_table_from(
+                        metrics.iter().map(|(name, value)| (name.as_str(), *value)),
+                    )?,
+                )?;
 
                 let draw_fn: LuaFunction = ctx.globals().get("Draw")?;
-                draw_fn.call(draw_ctx)?;
+                draw_fn.call::<_, ()>(draw_ctx)?;
                 anyhow::Ok(())
             })
         })?;
         Ok(())
     }
 
-    pub fn handle_event(&mut self, event: &Box<Event>) -> Result<()> {
+    /// Events the script doesn't want can be skipped without converting them to Lua
+    pub fn wants_event(&self, variant: &str) -> bool {
+        match &self.result {
+            Ok(DrawRuntimeData {
+                wanted_events: Some(wanted_events),
+                ..
+            }) => wanted_events.iter().any(|it| it == variant),
+            _ => true,
+        }
+    }
+
+    /// Calls the script's `ProcessEvent(event, meta)`
+    pub fn handle_event(&mut self, event: &Event, meta: &EventMeta) -> Result<()> {
         let DrawRuntimeData { lua, .. } = match &mut self.result {
             Ok(it) => it,
             Err(err) => return Err(anyhow!(err.0.clone())),
@@ -309,7 +339,8 @@ impl DrawRuntime {
 
             if let Some(handle_fn) = handle_fn {
                 let lua_event = to_lua(ctx, event)?;
-                handle_fn.call(lua_event)?;
+                let lua_meta = to_lua(ctx, meta)?;
+                handle_fn.call::<_, ()>((lua_event, lua_meta))?;
             }
 
             anyhow::Ok(())
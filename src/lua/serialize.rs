@@ -48,7 +48,7 @@ impl From<Error> for LuaSerializeError {
     }
 }
 
-impl<'lua, 'a> Serializer for &'a mut LuaSerializer<'lua> {
+impl<'lua> Serializer for &mut LuaSerializer<'lua> {
     type Ok = LuaValue<'lua>;
     type Error = LuaSerializeError;
 
@@ -128,9 +128,9 @@ impl<'lua, 'a> Serializer for &'a mut LuaSerializer<'lua> {
         Ok(LuaNil)
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(self)
     }
@@ -152,18 +152,18 @@ impl<'lua, 'a> Serializer for &'a mut LuaSerializer<'lua> {
         self.serialize_str(variant)
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T>(
         self,
         _name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
         _variant_index: u32,
@@ -171,7 +171,7 @@ impl<'lua, 'a> Serializer for &'a mut LuaSerializer<'lua> {
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let mut serializer = LuaTableSerializer::new(self.ctx)?;
         serializer.serialize_field("type", variant)?;
@@ -244,9 +244,9 @@ impl<'lua> ser::SerializeSeq for LuaListSerializer<'lua> {
     type Ok = LuaValue<'lua>;
     type Error = LuaSerializeError;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let index = self.current_value.len()?;
         self.current_value.set(
@@ -265,9 +265,9 @@ impl<'lua> ser::SerializeTuple for LuaListSerializer<'lua> {
     type Ok = LuaValue<'lua>;
     type Error = LuaSerializeError;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         ser::SerializeSeq::serialize_element(self, value)
     }
@@ -281,9 +281,9 @@ impl<'lua> ser::SerializeTupleStruct for LuaListSerializer<'lua> {
     type Ok = LuaValue<'lua>;
     type Error = LuaSerializeError;
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         ser::SerializeSeq::serialize_element(self, value)
     }
@@ -312,17 +312,17 @@ impl<'lua> ser::SerializeMap for LuaTableSerializer<'lua> {
     type Ok = LuaValue<'lua>;
     type Error = LuaSerializeError;
 
-    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         self.current_key = Some(key.serialize(&mut LuaSerializer::new(self.ctx))?);
         Ok(())
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let key = self
             .current_key
@@ -342,13 +342,9 @@ impl<'lua> ser::SerializeStruct for LuaTableSerializer<'lua> {
     type Ok = LuaValue<'lua>;
     type Error = LuaSerializeError;
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         ser::SerializeMap::serialize_entry(self, key, value)
     }
@@ -382,13 +378,9 @@ impl<'lua> ser::SerializeStructVariant for LuaVariantSerializer<'lua, LuaTableSe
     type Ok = LuaValue<'lua>;
     type Error = LuaSerializeError;
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         self.sub_serializer.serialize_field(key, value)
     }
@@ -403,9 +395,9 @@ impl<'lua> ser::SerializeTupleVariant for LuaVariantSerializer<'lua, LuaListSeri
     type Ok = LuaValue<'lua>;
     type Error = LuaSerializeError;
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         self.sub_serializer.serialize_field(value)
     }
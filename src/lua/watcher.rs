@@ -27,13 +27,13 @@ impl Watcher {
 
         Ok(Watcher {
             dirty_flag,
-            watcher: watcher,
+            watcher,
             currently_watching: vec![],
         })
     }
 
     pub fn is_dirty(&self) -> bool {
-        self.dirty_flag.lock().unwrap().clone()
+        *self.dirty_flag.lock().unwrap()
     }
 
     pub fn stop_watching(&mut self) -> Result<()> {
@@ -49,7 +49,7 @@ impl Watcher {
         *self.dirty_flag.lock().unwrap() = false;
         let loaded_modules = runtime.get_loaded_modules()?;
         for module_path in loaded_modules.iter() {
-            let watch_path = get_closest_existing_path(&module_path)?;
+            let watch_path = get_closest_existing_path(module_path)?;
 
             self.watcher
                 .watch(&watch_path, RecursiveMode::NonRecursive)?;
@@ -62,7 +62,7 @@ impl Watcher {
     }
 }
 
-fn get_closest_existing_path(module_path: &Path) -> Result<Cow<Path>> {
+fn get_closest_existing_path(module_path: &Path) -> Result<Cow<'_, Path>> {
     let mut current = Cow::Borrowed(module_path);
     while !current.exists() {
         current = current
@@ -1,86 +1,211 @@
+mod alloc_profile;
+mod bench;
 mod draw_utils;
 mod framework;
+mod list;
 mod load_algorithm;
 mod lua;
+mod metrics;
+mod output;
+mod playback;
 mod prelude;
 mod puzzles;
+mod recording;
+mod registry;
+mod run_all;
+mod table;
+mod tagged_json;
+mod timeline;
+mod variant_name;
+mod verify;
 
 use std::{
+    collections::BTreeMap,
+    io::{self, IsTerminal},
+    ops::Range,
+    path::{Path, PathBuf},
     sync::mpsc::{self, Receiver},
     thread,
+    time::{Duration, Instant},
 };
 
-use clap::{arg, Parser};
-use framework::{AsyncReportProgress, Event, NoOpReportProgress, ReportProgress};
+use clap::Parser;
+use framework::{
+    AsyncReportProgress, CancellationToken, Envelope, FanOutReportProgress, Level,
+    LevelFilterReportProgress, NoOpReportProgress, QueueCounts, QueuePolicy, ReportProgress,
+    ReportProgressExt, Stamper,
+};
 use ggez::{
     self,
     conf::{WindowMode, WindowSetup},
     glam::Vec2,
     graphics::{self, Color, DrawParam, Rect},
+    input::{
+        keyboard::{KeyCode, KeyInput},
+        mouse::MouseButton,
+    },
     ContextBuilder, GameError,
 };
-use load_algorithm::ThreadFunc;
+use load_algorithm::InputSource;
 use lua::draw_runtime::DrawRuntime;
 use lua::watcher::Watcher;
+use metrics::{Metrics, Progress};
+use output::{Format, Run};
+use playback::{Advance, Playback};
 use prelude::*;
+use recording::JsonLinesReportProgress;
+use timeline::Timeline;
+use variant_name::variant_name;
+
+/// Time per frame to spend receiving progress events, so a flood of them can't hold up the
+/// frame; anything left stays in the channel for the next one
+const INGEST_BUDGET: Duration = Duration::from_millis(3);
+/// Time per frame to spend applying progress events, separate from receiving them so that
+/// applying always gets its share
+const APPLY_BUDGET: Duration = Duration::from_millis(5);
+/// How often to print counters and gauges in `--no-window` mode
+const METRICS_INTERVAL: Duration = Duration::from_secs(1);
+/// How often to redraw the progress bar when stdout is a terminal
+const LIVE_METRICS_INTERVAL: Duration = Duration::from_millis(100);
+/// How often the solver hands its metrics over to be printed in `--no-window` mode
+const METRICS_BATCH_INTERVAL: Duration = Duration::from_millis(10);
 
 struct AppState {
     draw_runtime: DrawRuntime,
     watcher: Watcher,
-    event_receiver: Receiver<Box<Event>>,
-    events: Vec<Box<Event>>,
+    event_receiver: Receiver<Envelope>,
+    events: Vec<Envelope>,
     processing_error: Option<Error>,
+    cancellation: CancellationToken,
+    playback: Playback,
+    /// Digits typed so far for an event index to seek to
+    seek_input: String,
+    timeline: Timeline,
+    /// Events still in the channel, and events the solver skipped sending because the queue
+    /// was full
+    queue: QueueCounts,
+    /// Event variants to pass to the script, from `--events`, when replaying; live events are
+    /// filtered before they're queued
+    event_filter: Option<Vec<String>>,
+    metrics: Metrics,
+    /// Counters and gauges as of the start of this frame
+    frame_metrics: BTreeMap<String, f64>,
+    frame_progress: Option<Progress>,
+}
+
+impl AppState {
+    fn restart_runtime(&mut self) -> Result<(), GameError> {
+        self.watcher
+            .stop_watching()
+            .map_err(|err| GameError::CustomError(err.to_string()))?;
+        let runtime_ref = &mut self.draw_runtime;
+        *runtime_ref = runtime_ref.restart();
+        self.watcher
+            .start_watching(runtime_ref)
+            .map_err(|err| GameError::CustomError(err.to_string()))?;
+        self.processing_error = None;
+        Ok(())
+    }
+
+    /// Applies events until the apply budget runs out, returning the position reached. At
+    /// least one event is applied each frame so playback always makes progress.
+    fn apply_events(&mut self, range: Range<usize>, deadline: Instant) -> usize {
+        for index in range.clone() {
+            if self.processing_error.is_some() {
+                break;
+            }
+            if index > range.start && Instant::now() >= deadline {
+                return index;
+            }
+            let variant = self.timeline.kind(index);
+            let wanted = self
+                .event_filter
+                .as_ref()
+                .is_none_or(|it| it.iter().any(|wanted| wanted == variant))
+                && self.draw_runtime.wants_event(variant);
+            if !wanted {
+                continue;
+            }
+            let Envelope { event, meta } = &self.events[index];
+            if let Err(err) = self.draw_runtime.handle_event(event.as_ref(), meta) {
+                self.processing_error = Some(err);
+            }
+        }
+        range.end
+    }
+
+    fn hud_text(&self) -> String {
+        let mut parts = vec![format!(
+            "event {}/{}",
+            self.playback.position(),
+            self.events.len()
+        )];
+        let backlog = self.events.len() - self.playback.position() + self.queue.queued();
+        if backlog > 0 {
+            parts.push(format!("{backlog} queued"));
+        }
+        let dropped = self.queue.dropped();
+        if dropped > 0 {
+            parts.push(format!("{dropped} dropped"));
+        }
+        if let Some(progress) = self.frame_progress {
+            parts.push(match progress.eta {
+                Some(eta) => format!("{:.0}% done, ETA {eta:.1?}", progress.fraction * 100.0),
+                None => format!("{:.0}% done", progress.fraction * 100.0),
+            });
+        }
+        parts.push(match self.playback.rate {
+            Some(rate) => format!("{rate}/s"),
+            None => "max speed".to_string(),
+        });
+        if self.playback.paused {
+            parts.push("paused".to_string());
+        }
+        if !self.seek_input.is_empty() {
+            parts.push(format!("seek to {}_", self.seek_input));
+        }
+        parts.join(" | ")
+    }
 }
 
 impl ggez::event::EventHandler<GameError> for AppState {
-    fn update(&mut self, _ctx: &mut ggez::Context) -> Result<(), GameError> {
+    fn update(&mut self, ctx: &mut ggez::Context) -> Result<(), GameError> {
         if self.watcher.is_dirty() {
             println!("Reloading Lua...");
-            self.watcher
-                .stop_watching()
-                .map_err(|err| GameError::CustomError(err.to_string()))?;
-            let mut runtime_ref = &mut self.draw_runtime;
-            *runtime_ref = runtime_ref.restart();
-            self.watcher
-                .start_watching(&mut runtime_ref)
-                .map_err(|err| GameError::CustomError(err.to_string()))?;
-
-            self.processing_error = None;
-
-            if self.events.len() > 0 {
-                println!("Replaying progress events...");
-                for event in self.events.iter() {
-                    if let Err(err) = self.draw_runtime.handle_event(event) {
-                        self.processing_error = Some(err);
-                        break;
-                    }
-                }
-                println!("Progress events done!");
+            self.restart_runtime()?;
+            if self.playback.position() > 0 {
+                // replayed over the next few frames, within the apply budget
+                println!("Replaying {} progress events...", self.playback.position());
+                self.playback.applied_until(0);
             }
-
             println!("Reloaded!");
-        } else if self.processing_error.is_none() {
-            let mut new_events = vec![];
-            // read until the queue is empty
-            // TODO: or maybe until frame budget is exceeded
-            loop {
-                let new_event = self.event_receiver.try_recv().ok();
-                if let Some(new_event) = new_event {
-                    new_events.push(new_event);
-                } else {
-                    break;
-                }
-            }
+            return Ok(());
+        }
 
-            for new_event in new_events {
-                if self.processing_error.is_none() {
-                    if let Err(err) = self.draw_runtime.handle_event(&new_event) {
-                        self.processing_error = Some(err);
-                    }
+        let ingest_deadline = Instant::now() + INGEST_BUDGET;
+        while Instant::now() < ingest_deadline {
+            match self.event_receiver.try_recv() {
+                Ok(new_event) => {
+                    self.queue.received();
+                    self.timeline.push(new_event.event.as_ref());
+                    self.events.push(new_event);
                 }
-                self.events.push(new_event);
+                Err(_) => break,
             }
         }
+
+        self.frame_metrics = self.metrics.snapshot();
+        self.frame_progress = self.metrics.progress();
+
+        let deadline = Instant::now() + APPLY_BUDGET;
+        let reached = match self.playback.advance(ctx.time.delta(), self.events.len()) {
+            Advance::Apply(range) => self.apply_events(range, deadline),
+            Advance::Rebuild(count) => {
+                self.restart_runtime()?;
+                self.apply_events(0..count, deadline)
+            }
+        };
+        self.playback.applied_until(reached);
         Ok(())
     }
 
@@ -88,7 +213,7 @@ impl ggez::event::EventHandler<GameError> for AppState {
         let runtime = &mut self.draw_runtime;
         let mut canvas = graphics::Canvas::from_frame(ctx, draw_utils::WHITE);
 
-        let draw_result = runtime.draw(ctx, &mut canvas);
+        let draw_result = runtime.draw(ctx, &mut canvas, &self.frame_metrics);
         let draw_error = draw_result.err();
         let error_text = self
             .processing_error
@@ -111,58 +236,476 @@ impl ggez::event::EventHandler<GameError> for AppState {
             let mut text = graphics::Text::new(error_text);
             text.set_scale(16.0);
             canvas.draw(
-                &mut text,
+                &text,
                 DrawParam::default()
                     .dest(Vec2::new(8.0, 8.0))
                     .color(draw_utils::RED.to_owned()),
             );
         }
 
+        self.timeline
+            .draw(ctx, &mut canvas, self.playback.position())?;
+        let mut hud = graphics::Text::new(self.hud_text());
+        hud.set_scale(14.0);
+        let size = ctx.gfx.drawable_size();
+        canvas.draw(
+            &hud,
+            DrawParam::default()
+                .dest(Vec2::new(8.0, size.1 - Timeline::HEIGHT - 20.0))
+                .color(draw_utils::BLACK),
+        );
+        if let Some(progress) = self.frame_progress {
+            // a thin bar just above the HUD text
+            let y = size.1 - Timeline::HEIGHT - 24.0;
+            canvas.draw(
+                &graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0.0, y, size.0 * progress.fraction as f32, 3.0),
+                    Color::from_rgb(0x4C, 0xAF, 0x50),
+                )?,
+                DrawParam::default(),
+            );
+        }
+
         canvas.finish(ctx)?;
         Ok(())
     }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut ggez::Context,
+        input: KeyInput,
+        _repeated: bool,
+    ) -> Result<(), GameError> {
+        match input.keycode {
+            // overriding key_down_event replaces ggez's default handler, which quits on Escape
+            Some(KeyCode::Escape) => ctx.request_quit(),
+            Some(KeyCode::Space) => self.playback.toggle_pause(),
+            Some(KeyCode::Right) => self.playback.step(),
+            Some(KeyCode::Left) => self.playback.step_back(),
+            Some(KeyCode::Up) => self.playback.faster(),
+            Some(KeyCode::Down) => self.playback.slower(),
+            Some(KeyCode::Home) => self.playback.seek(0),
+            Some(KeyCode::End) => self.playback.seek(usize::MAX),
+            Some(KeyCode::Back) => {
+                self.seek_input.pop();
+            }
+            Some(KeyCode::Return) => {
+                if let Ok(position) = self.seek_input.parse() {
+                    self.playback.seek(position);
+                }
+                self.seek_input.clear();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn text_input_event(
+        &mut self,
+        _ctx: &mut ggez::Context,
+        character: char,
+    ) -> Result<(), GameError> {
+        if character.is_ascii_digit() {
+            self.seek_input.push(character);
+        }
+        Ok(())
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut ggez::Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) -> Result<(), GameError> {
+        if button == MouseButton::Left {
+            if let Some(position) = self
+                .timeline
+                .mouse_down(ctx, x, y, self.playback.position())
+            {
+                self.playback.paused = true;
+                self.playback.seek(position);
+            }
+        }
+        Ok(())
+    }
+
+    fn mouse_motion_event(
+        &mut self,
+        ctx: &mut ggez::Context,
+        x: f32,
+        _y: f32,
+        _dx: f32,
+        _dy: f32,
+    ) -> Result<(), GameError> {
+        if let Some(position) = self.timeline.mouse_moved(ctx, x) {
+            self.playback.seek(position);
+        }
+        Ok(())
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut ggez::Context,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) -> Result<(), GameError> {
+        self.timeline.mouse_up();
+        Ok(())
+    }
+
+    fn quit_event(&mut self, _ctx: &mut ggez::Context) -> Result<bool, GameError> {
+        self.cancellation.cancel();
+        Ok(false)
+    }
 }
 
 #[derive(clap::Parser, Debug)]
-#[command(author, version, about)]
+#[command(
+    author,
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    day: String,
-    part: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[arg(required = true)]
+    day: Option<String>,
+    #[arg(required = true)]
+    part: Option<String>,
     #[arg(long)]
     no_window: bool,
+    /// Path to the puzzle input, or - for stdin; defaults to inputs/<day>.txt
+    #[arg(long)]
+    input: Option<PathBuf>,
+    /// Run against one of the puzzle's example inputs instead; defaults to the first one
+    #[arg(long, conflicts_with = "input")]
+    sample: Option<Option<String>>,
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+    /// Cancel the run if it takes longer than this many seconds
+    #[arg(long)]
+    timeout: Option<f64>,
+    /// Count allocations made while parsing and solving instead of running normally; needs a
+    /// build with `--features alloc-profile`
+    #[arg(long)]
+    profile_alloc: bool,
+    /// Write every progress event to this JSON Lines file, or to stdout if it's `-`
+    #[arg(long)]
+    record: Option<PathBuf>,
+    /// Visualize progress events from a recording instead of running the solver
+    #[arg(long, conflicts_with_all = ["record", "input", "sample", "no_window", "profile_alloc"])]
+    replay: Option<PathBuf>,
+    /// Send replayed events with their original timing
+    #[arg(long, requires = "replay")]
+    realtime: bool,
+    /// Progress events to visualize per second; defaults to as fast as they arrive
+    #[arg(long)]
+    rate: Option<f64>,
+    /// What to do when the solver outpaces the visualizer: block, sample:<n> or coalesce
+    #[arg(long, default_value = "block")]
+    queue_policy: QueuePolicy,
+    /// Progress events to buffer between the solver and the visualizer
+    #[arg(
+        long,
+        default_value_t = 10_000,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    queue_capacity: usize,
+    /// Only pass these event variants on, e.g. AnalyzePair,ContainsOther. Applies before
+    /// recording and before the script sees them, with or without a window; replays skip the
+    /// other variants when applying them
+    #[arg(long, value_delimiter = ',')]
+    events: Option<Vec<String>>,
+    /// Drop events reported below this level, before they're recorded or visualized
+    #[arg(long, value_enum, default_value_t = Level::Trace)]
+    level: Level,
 }
 
-fn execute_and_print(thread_func: &ThreadFunc, report_progress: &Box<dyn ReportProgress>) {
-    let result = thread_func(report_progress);
-    match result {
-        Ok(result) => println!("Result: {result}"),
-        Err(err) => eprintln!("Error: {err}"),
-    }
+/// Visualizer settings shared by live runs and replays
+struct WindowOptions {
+    rate: Option<f64>,
+    /// Taken by live runs, which filter events before they reach the window
+    event_filter: Option<Vec<String>>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// List every puzzle with its visualization script, progress events and input status
+    List,
+    /// Run every puzzle without a window and print a table of answers and timings
+    All {
+        /// Number of puzzles to run at once; defaults to the number of CPUs
+        #[arg(long, default_value_t = run_all::default_jobs())]
+        jobs: usize,
+    },
+    /// Time a puzzle over many iterations, reporting parse and solve separately
+    Bench {
+        day: String,
+        part: String,
+        #[arg(long, default_value_t = 100)]
+        iterations: usize,
+        #[arg(long, default_value_t = 10)]
+        warmup: usize,
+        /// Path to the puzzle input, or - for stdin; defaults to inputs/<day>.txt
+        #[arg(long)]
+        input: Option<PathBuf>,
+        /// Also write the results as JSON to this path
+        #[arg(long)]
+        json: Option<PathBuf>,
+    },
+    /// Check every puzzle against its recorded answers
+    Verify {
+        #[arg(long, default_value = "answers.toml")]
+        answers: PathBuf,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
-    let algorithm = load_algorithm::load(&args.day, &args.part)?;
+    match args.command {
+        Some(Command::List) => {
+            list::print_list();
+            return Ok(());
+        }
+        Some(Command::All { jobs }) => {
+            let runs = run_all::run_all(jobs);
+            run_all::print_table(&runs);
+            if runs.iter().any(|run| run.result.is_err()) {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Bench {
+            day,
+            part,
+            iterations,
+            warmup,
+            input,
+            json,
+        }) => {
+            let solver = load_algorithm::load_solver(&day, &part)?;
+            let input = load_algorithm::load_input(&day, input.as_deref())?;
+            let report = bench::bench(&day, &part, &solver, &input, warmup, iterations)?;
+            bench::print_report(&report);
+            if let Some(json) = json {
+                bench::write_json(&report, &json)?;
+            }
+            return Ok(());
+        }
+        Some(Command::Verify { answers }) => {
+            if !verify::run(&answers)? {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        None => {}
+    }
+
+    let (day, part) = (args.day.unwrap(), args.part.unwrap());
+    let input_source = match (args.sample, args.input) {
+        (Some(sample), _) => InputSource::Sample(sample),
+        (None, Some(path)) => InputSource::File(path),
+        (None, None) => InputSource::Default,
+    };
+
+    if args.profile_alloc {
+        let (puzzle, part) = registry::lookup(&day, &part)?;
+        let (input, _) = load_algorithm::resolve_input(&puzzle, part.name, &input_source)?;
+        let report = alloc_profile::profile(puzzle.day, part.name, &part.solver, &input)?;
+        match args.format {
+            Format::Text => alloc_profile::print_report(&report),
+            Format::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        }
+        return Ok(());
+    }
+
+    let mut window_options = WindowOptions {
+        rate: args.rate,
+        event_filter: args.events,
+    };
+
+    if let Some(replay) = args.replay {
+        let (puzzle, part) = registry::lookup(&day, &part)?;
+        let recorded = recording::load_recording(&replay)?;
+        let (event_sender, event_receiver) = mpsc::channel();
+        let metrics = Metrics::default();
+        let queue = QueueCounts::default();
+        if args.realtime {
+            let (queue, metrics) = (queue.clone(), metrics.clone());
+            thread::spawn(move || {
+                recording::replay_realtime(recorded, event_sender, queue, metrics)
+            });
+        } else {
+            for recorded in recorded {
+                recorded.replay(&event_sender, &queue, &metrics);
+            }
+        }
+        let draw_runtime = DrawRuntime::new(&load_algorithm::script_path(puzzle.day, part.name));
+        return run_window(
+            draw_runtime,
+            event_receiver,
+            CancellationToken::default(),
+            window_options,
+            queue,
+            metrics,
+            || {},
+        );
+    }
+
+    let algorithm = load_algorithm::load(&day, &part, &input_source)?;
+    let cancellation = CancellationToken::default();
+    let stamper = Stamper::default();
+    let run = Run {
+        day: algorithm.day,
+        part: algorithm.part,
+        thread_func: algorithm.thread_func,
+        input: algorithm.input,
+        expected: algorithm.expected,
+        cancellation: cancellation.clone(),
+        stamper: stamper.clone(),
+        timeout: args.timeout.map(Duration::from_secs_f64),
+    };
 
     if args.no_window {
-        let report_progress: Box<dyn ReportProgress> = Box::new(NoOpReportProgress);
-        execute_and_print(&algorithm.thread_func, &report_progress);
+        let report_progress: Box<dyn ReportProgress + Send> = match args.record.as_deref() {
+            Some(path) => Box::new(JsonLinesReportProgress::create(path, stamper)?),
+            None => Box::new(NoOpReportProgress),
+        };
+        let metrics = Metrics::default();
+        let report_progress = FanOutReportProgress {
+            sinks: vec![
+                report_progress,
+                Box::new(metrics.batched(METRICS_BATCH_INTERVAL)),
+            ],
+        };
+        let progress_on_stdout = args.record.as_deref() == Some(Path::new("-"));
+        // the live line shares stdout with the answer, so it's cleared before that's printed
+        let live = io::stdout().is_terminal() && !progress_on_stdout;
+        let interval = if live {
+            LIVE_METRICS_INTERVAL
+        } else {
+            METRICS_INTERVAL
+        };
+        let report_progress = with_level(
+            args.level,
+            with_event_filter(window_options.event_filter, Box::new(report_progress)),
+        );
+        metrics::print_while(&metrics, interval, live, || {
+            output::execute_timed(run, args.format, report_progress)
+        })
+        .print(args.format, progress_on_stdout);
         return Ok(());
     }
 
-    let (event_sender, event_receiver) = mpsc::channel::<Box<framework::Event>>();
-    let mut initial_state = AppState {
-        draw_runtime: algorithm.draw_runtime,
-        watcher: Watcher::new()?,
+    if !algorithm.emits_progress {
+        println!("{day} {part} doesn't report any progress events to visualize");
+    }
+
+    let (async_report_progress, event_receiver) =
+        AsyncReportProgress::new(args.queue_policy, args.queue_capacity);
+    let queue = async_report_progress.counts.clone();
+    let metrics = async_report_progress.metrics.clone();
+    let report_progress = with_level(
+        args.level,
+        with_event_filter(
+            window_options.event_filter.take(),
+            with_recording(
+                args.record.as_deref(),
+                stamper,
+                Box::new(async_report_progress),
+            )?,
+        ),
+    );
+    let format = args.format;
+    let progress_on_stdout = args.record.as_deref() == Some(Path::new("-"));
+    run_window(
+        algorithm.draw_runtime,
+        event_receiver,
+        cancellation,
+        window_options,
+        queue,
+        metrics,
+        move || {
+            thread::spawn(move || {
+                output::execute_and_print(run, format, report_progress, progress_on_stdout)
+            });
+        },
+    )
+}
+
+/// Only passes on the event variants named by `--events`
+fn with_event_filter(
+    variants: Option<Vec<String>>,
+    report_progress: Box<dyn ReportProgress + Send>,
+) -> Box<dyn ReportProgress + Send> {
+    match variants {
+        Some(variants) => Box::new(report_progress.filter(move |_, event| {
+            let name = variant_name(event);
+            variants.iter().any(|it| *it == name)
+        })),
+        None => report_progress,
+    }
+}
+
+fn with_recording(
+    record: Option<&Path>,
+    stamper: Stamper,
+    report_progress: Box<dyn ReportProgress + Send>,
+) -> Result<Box<dyn ReportProgress + Send>> {
+    Ok(match record {
+        Some(path) => Box::new(recording::record(path, stamper, report_progress)?),
+        None => report_progress,
+    })
+}
+
+fn with_level(
+    min_level: Level,
+    report_progress: Box<dyn ReportProgress + Send>,
+) -> Box<dyn ReportProgress + Send> {
+    match min_level {
+        Level::Trace => report_progress,
+        min_level => Box::new(LevelFilterReportProgress {
+            inner: report_progress,
+            min_level,
+        }),
+    }
+}
+
+/// Opens the visualizer window, calling `start` once it's ready for events
+fn run_window(
+    mut draw_runtime: DrawRuntime,
+    event_receiver: Receiver<Envelope>,
+    cancellation: CancellationToken,
+    options: WindowOptions,
+    queue: QueueCounts,
+    metrics: Metrics,
+    start: impl FnOnce(),
+) -> Result<()> {
+    let mut watcher = Watcher::new()?;
+    watcher.start_watching(&mut draw_runtime)?;
+    let initial_state = AppState {
+        draw_runtime,
+        watcher,
         event_receiver,
         events: vec![],
         processing_error: None,
+        cancellation,
+        playback: Playback::new(options.rate),
+        seek_input: String::new(),
+        timeline: Timeline::default(),
+        queue,
+        event_filter: options.event_filter,
+        metrics,
+        frame_metrics: BTreeMap::new(),
+        frame_progress: None,
     };
 
-    initial_state
-        .watcher
-        .start_watching(&mut initial_state.draw_runtime)?;
-
     let conf = ggez::conf::Conf::new();
     let (ctx, event_loop) = ContextBuilder::new("aoc2022", "dallonf")
         .default_conf(conf)
@@ -176,12 +719,7 @@ fn main() -> anyhow::Result<()> {
         .build()
         .unwrap();
 
-    thread::spawn(move || {
-        let report_progress: Box<dyn ReportProgress> = Box::new(AsyncReportProgress {
-            sender: event_sender,
-        });
-        execute_and_print(&algorithm.thread_func, &report_progress);
-    });
+    start();
 
     ggez::event::run(ctx, event_loop, initial_state);
 }
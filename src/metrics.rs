@@ -0,0 +1,391 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use erased_serde::Serialize;
+use serde::Deserialize;
+
+use crate::framework::{Level, ReportProgress};
+use crate::prelude::*;
+
+const LIVE_BAR_WIDTH: usize = 30;
+/// Distinct names a [`BatchedMetrics`] holds before it hands them over early
+const BATCH_NAMES: usize = 16;
+/// Moves back to the start of the line and erases it
+const CLEAR_LINE: &str = "\r\x1b[2K";
+
+/// A single change to a named number, as written to recordings
+#[derive(Clone, Debug, PartialEq, serde::Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    Counter { name: String, delta: i64 },
+    Gauge { name: String, value: f64 },
+    Fraction { done: u64, total: u64 },
+}
+
+/// How far along the solver says it is
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    pub fraction: f64,
+    /// Time left if the rest goes as fast as what's been done so far
+    pub eta: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    values: BTreeMap<String, f64>,
+    fraction: Option<(u64, u64)>,
+}
+
+impl MetricsState {
+    fn add(&mut self, name: &str, delta: i64) {
+        match self.values.get_mut(name) {
+            Some(value) => *value += delta as f64,
+            None => {
+                self.values.insert(name.to_string(), delta as f64);
+            }
+        }
+    }
+
+    fn set(&mut self, name: &str, value: f64) {
+        match self.values.get_mut(name) {
+            Some(current) => *current = value,
+            None => {
+                self.values.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
+/// Running totals of counters, latest values of gauges and the latest reported fraction done,
+/// shared between the solver and whatever displays them
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    state: Arc<Mutex<MetricsState>>,
+    start: Instant,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics {
+            state: Arc::default(),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Metrics {
+    pub fn add(&self, name: &str, delta: i64) {
+        self.state.lock().unwrap().add(name, delta)
+    }
+
+    pub fn set(&self, name: &str, value: f64) {
+        self.state.lock().unwrap().set(name, value)
+    }
+
+    pub fn set_fraction(&self, done: u64, total: u64) {
+        self.state.lock().unwrap().fraction = Some((done, total));
+    }
+
+    pub fn apply(&self, metric: &Metric) {
+        match metric {
+            Metric::Counter { name, delta } => self.add(name, *delta),
+            Metric::Gauge { name, value } => self.set(name, *value),
+            Metric::Fraction { done, total } => self.set_fraction(*done, *total),
+        }
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, f64> {
+        self.state.lock().unwrap().values.clone()
+    }
+
+    pub fn progress(&self) -> Option<Progress> {
+        let (done, total) = self.state.lock().unwrap().fraction?;
+        let fraction = if total == 0 {
+            1.0
+        } else {
+            (done as f64 / total as f64).clamp(0.0, 1.0)
+        };
+        let eta =
+            (fraction > 0.0).then(|| self.start.elapsed().mul_f64((1.0 - fraction) / fraction));
+        Some(Progress { fraction, eta })
+    }
+
+    /// A sink for a single solver thread that only passes its metrics on every `interval`
+    pub fn batched(&self, interval: Duration) -> BatchedMetrics {
+        BatchedMetrics {
+            metrics: self.clone(),
+            interval,
+            batch: RefCell::new(Batch {
+                changes: Vec::with_capacity(BATCH_NAMES),
+                fraction: None,
+                flushed: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        self.state
+            .lock()
+            .unwrap()
+            .values
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .join(" ")
+    }
+}
+
+/// Keeps track of metrics and ignores events
+impl ReportProgress for Metrics {
+    fn report_progress(&self, _data: Box<dyn Serialize + Send>) {}
+
+    fn is_enabled(&self, _level: Level) -> bool {
+        false
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.add(name, delta)
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.set(name, value)
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        self.set_fraction(done, total)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Change {
+    Add(i64),
+    Set(f64),
+}
+
+#[derive(Debug)]
+struct Batch {
+    changes: Vec<(&'static str, Change)>,
+    fraction: Option<(u64, u64)>,
+    flushed: Instant,
+}
+
+/// Collects metrics on the solver's thread and hands them to the shared [`Metrics`] under a
+/// single lock every `interval`, so reporting one doesn't lock or allocate
+#[derive(Debug)]
+pub struct BatchedMetrics {
+    metrics: Metrics,
+    interval: Duration,
+    batch: RefCell<Batch>,
+}
+
+impl BatchedMetrics {
+    fn change(&self, name: &'static str, change: impl Fn(Option<Change>) -> Change) {
+        let mut batch = self.batch.borrow_mut();
+        match batch.changes.iter_mut().find(|(it, _)| *it == name) {
+            Some((_, current)) => *current = change(Some(*current)),
+            None => {
+                if batch.changes.len() == BATCH_NAMES {
+                    self.hand_over(&mut batch);
+                }
+                batch.changes.push((name, change(None)));
+            }
+        }
+        self.hand_over_if_due(&mut batch);
+    }
+
+    fn hand_over_if_due(&self, batch: &mut Batch) {
+        if batch.flushed.elapsed() >= self.interval {
+            self.hand_over(batch);
+        }
+    }
+
+    fn hand_over(&self, batch: &mut Batch) {
+        let mut state = self.metrics.state.lock().unwrap();
+        for (name, change) in batch.changes.drain(..) {
+            match change {
+                Change::Add(delta) => state.add(name, delta),
+                Change::Set(value) => state.set(name, value),
+            }
+        }
+        if let Some(fraction) = batch.fraction.take() {
+            state.fraction = Some(fraction);
+        }
+        batch.flushed = Instant::now();
+    }
+}
+
+impl ReportProgress for BatchedMetrics {
+    fn report_progress(&self, _data: Box<dyn Serialize + Send>) {}
+
+    fn is_enabled(&self, _level: Level) -> bool {
+        false
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.change(name, |current| match current {
+            Some(Change::Add(total)) => Change::Add(total + delta),
+            Some(Change::Set(value)) => Change::Set(value + delta as f64),
+            None => Change::Add(delta),
+        })
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.change(name, |_| Change::Set(value))
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        let mut batch = self.batch.borrow_mut();
+        batch.fraction = Some((done, total));
+        self.hand_over_if_due(&mut batch);
+    }
+
+    fn flush(&self) {
+        self.hand_over(&mut self.batch.borrow_mut())
+    }
+}
+
+impl Drop for BatchedMetrics {
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+/// Renders e.g. `[#######-------------]  35% ETA 4.2s`
+pub fn progress_bar(progress: &Progress, width: usize) -> String {
+    let filled = (progress.fraction * width as f64).round() as usize;
+    let eta = match progress.eta {
+        Some(eta) => format!("{eta:.1?}"),
+        None => "?".to_string(),
+    };
+    format!(
+        "[{}{}] {:>3.0}% ETA {eta}",
+        "#".repeat(filled),
+        "-".repeat(width - filled),
+        progress.fraction * 100.0
+    )
+}
+
+/// Prints the metrics to stderr every `interval` while `f` runs, and once more at the end.
+/// With `live` set, they're instead shown after a progress bar on a line of stdout that's
+/// redrawn in place, for when stdout is a terminal.
+pub fn print_while<T>(
+    metrics: &Metrics,
+    interval: Duration,
+    live: bool,
+    f: impl FnOnce() -> T,
+) -> T {
+    let print = || {
+        let summary = metrics.summary();
+        if !summary.is_empty() {
+            eprintln!("Metrics: {summary}");
+        }
+    };
+    let print_live = || {
+        let bar = metrics
+            .progress()
+            .map(|progress| progress_bar(&progress, LIVE_BAR_WIDTH));
+        let line = bar.into_iter().chain([metrics.summary()]).join(" | ");
+        print!("{CLEAR_LINE}{line}");
+        let _ = io::stdout().flush();
+    };
+    let (done_sender, done) = mpsc::channel::<()>();
+    let result = thread::scope(|scope| {
+        scope.spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = done.recv_timeout(interval) {
+                if live {
+                    print_live();
+                } else {
+                    print();
+                }
+            }
+            if live {
+                print!("{CLEAR_LINE}");
+                let _ = io::stdout().flush();
+            }
+        });
+        let result = f();
+        drop(done_sender);
+        result
+    });
+    print();
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn counters_add_and_gauges_replace() {
+        let metrics = Metrics::default();
+        metrics.report_counter("pairs", 1);
+        metrics.report_counter("pairs", 2);
+        metrics.report_gauge("depth", 4.0);
+        metrics.apply(&Metric::Gauge {
+            name: "depth".to_string(),
+            value: 2.5,
+        });
+        assert_eq!(
+            metrics.snapshot(),
+            BTreeMap::from([("depth".to_string(), 2.5), ("pairs".to_string(), 3.0)])
+        );
+        assert_eq!(metrics.summary(), "depth=2.5 pairs=3");
+    }
+
+    #[test]
+    fn progress_bar_and_eta() {
+        let metrics = Metrics::default();
+        assert_eq!(metrics.progress(), None);
+        metrics.report_fraction(0, 4);
+        assert_eq!(
+            metrics.progress(),
+            Some(Progress {
+                fraction: 0.0,
+                eta: None
+            })
+        );
+        let progress = Progress {
+            fraction: 0.25,
+            eta: Some(Duration::from_millis(4200)),
+        };
+        assert_eq!(progress_bar(&progress, 8), "[##------]  25% ETA 4.2s");
+        metrics.report_fraction(4, 4);
+        assert_eq!(metrics.progress().unwrap().eta, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn batched_metrics_wait_for_a_flush() {
+        let metrics = Metrics::default();
+        metrics.report_counter("pairs", 1);
+        let batched = metrics.batched(Duration::MAX);
+        {
+            // reporting would deadlock if it took the lock
+            let _state = metrics.state.lock().unwrap();
+            batched.report_counter("pairs", 1);
+            batched.report_counter("pairs", 2);
+            batched.report_gauge("depth", 4.0);
+            batched.report_counter("depth", 1);
+            batched.report_fraction(1, 2);
+        }
+        assert_eq!(metrics.summary(), "pairs=1");
+        batched.flush();
+        assert_eq!(metrics.summary(), "depth=5 pairs=4");
+        assert_eq!(metrics.progress().unwrap().fraction, 0.5);
+
+        for name in [
+            "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o",
+        ] {
+            batched.report_counter(name, 1);
+        }
+        batched.report_counter("pairs", 1);
+        assert_eq!(metrics.snapshot()["pairs"], 4.0);
+        batched.report_counter("p", 1);
+        assert_eq!(metrics.snapshot()["pairs"], 5.0);
+        drop(batched);
+        assert_eq!(metrics.snapshot()["p"], 1.0);
+    }
+}
@@ -0,0 +1,252 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::framework::{
+    CancellableReportProgress, CancellationToken, Cancelled, CountingReportProgress,
+    ReportProgress, Stamper, StampingReportProgress,
+};
+use crate::load_algorithm::ThreadFunc;
+use crate::prelude::*;
+
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub day: &'static str,
+    pub part: &'static str,
+    pub answer: Option<String>,
+    /// Known answer, if the input is one of the puzzle's samples
+    pub expected: Option<&'static str>,
+    pub duration_ns: u64,
+    pub progress_events: usize,
+    pub error: Option<ErrorReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    /// The error followed by each of its causes
+    pub chain: Vec<String>,
+    /// Only captured when RUST_BACKTRACE or RUST_LIB_BACKTRACE is set
+    pub backtrace: Option<String>,
+}
+
+impl From<&Error> for ErrorReport {
+    fn from(err: &Error) -> Self {
+        let backtrace = err.backtrace().to_string();
+        ErrorReport {
+            chain: err.chain().map(|it| it.to_string()).collect(),
+            backtrace: match backtrace.as_str() {
+                "" | "disabled backtrace" | "unsupported backtrace" => None,
+                _ => Some(backtrace),
+            },
+        }
+    }
+}
+
+pub struct Run {
+    pub day: &'static str,
+    pub part: &'static str,
+    pub thread_func: ThreadFunc,
+    pub input: String,
+    pub expected: Option<&'static str>,
+    pub cancellation: CancellationToken,
+    /// Stamps the run's events, with its clock started as the solver starts
+    pub stamper: Stamper,
+    /// Cancel the run and report an error if it takes longer than this
+    pub timeout: Option<Duration>,
+}
+
+/// Runs the algorithm on its own thread so it can be abandoned if it times out
+fn execute(run: Run, report_progress: Box<dyn ReportProgress + Send>) -> Result<String> {
+    let Run {
+        thread_func,
+        input,
+        cancellation,
+        stamper,
+        timeout,
+        ..
+    } = run;
+    let (sender, receiver) = mpsc::channel();
+    let thread_cancellation = cancellation.clone();
+    thread::spawn(move || {
+        stamper.start();
+        let report_progress: Box<dyn ReportProgress> = Box::new(CancellableReportProgress {
+            inner: Box::new(StampingReportProgress {
+                inner: report_progress,
+                stamper,
+            }),
+            cancellation: thread_cancellation,
+        });
+        let result = thread_func(&input, &report_progress);
+        report_progress.flush();
+        // the receiver is gone if the run already timed out
+        let _ = sender.send(result);
+    });
+
+    let result = match timeout {
+        Some(timeout) => match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => {
+                cancellation.cancel();
+                bail!("Timed out after {timeout:?}");
+            }
+            Err(RecvTimeoutError::Disconnected) => bail!("Solver panicked"),
+        },
+        None => receiver
+            .recv()
+            .unwrap_or_else(|_| Err(anyhow!("Solver panicked"))),
+    };
+    if cancellation.is_cancelled() {
+        bail!(Cancelled);
+    }
+    result
+}
+
+/// What happened in a run, kept until it's printed
+pub struct Outcome {
+    pub day: &'static str,
+    pub part: &'static str,
+    pub expected: Option<&'static str>,
+    pub result: Result<String>,
+    pub duration: Duration,
+    pub progress_events: usize,
+}
+
+/// Runs the algorithm and times it. Progress events are only counted for `Format::Json`,
+/// the one that prints the count, as counting means building every event.
+pub fn execute_timed(
+    run: Run,
+    format: Format,
+    report_progress: Box<dyn ReportProgress + Send>,
+) -> Outcome {
+    let (day, part, expected) = (run.day, run.part, run.expected);
+    let count = Arc::new(AtomicUsize::new(0));
+    let report_progress: Box<dyn ReportProgress + Send> = match format {
+        Format::Json => Box::new(CountingReportProgress {
+            inner: report_progress,
+            count: count.clone(),
+        }),
+        Format::Text => report_progress,
+    };
+    let start = Instant::now();
+    let result = execute(run, report_progress);
+    Outcome {
+        day,
+        part,
+        expected,
+        result,
+        duration: start.elapsed(),
+        progress_events: count.load(Ordering::Relaxed),
+    }
+}
+
+impl Outcome {
+    /// Prints to stderr instead when `progress_on_stdout` is set, so the two don't mix
+    pub fn print(&self, format: Format, progress_on_stdout: bool) {
+        let print = |text: &str| {
+            if progress_on_stdout {
+                eprintln!("{text}");
+            } else {
+                println!("{text}");
+            }
+        };
+        match format {
+            Format::Text => {
+                match &self.result {
+                    Ok(result) => print(&format!("Result: {result}")),
+                    Err(err) => eprintln!("Error: {err}"),
+                }
+                if let Some(expected) = self.expected {
+                    print(&format!("Expected: {expected}"));
+                }
+            }
+            Format::Json => {
+                let report = RunReport {
+                    day: self.day,
+                    part: self.part,
+                    answer: self.result.as_ref().ok().cloned(),
+                    expected: self.expected,
+                    duration_ns: self.duration.as_nanos() as u64,
+                    progress_events: self.progress_events,
+                    error: self.result.as_ref().err().map(ErrorReport::from),
+                };
+                match serde_json::to_string_pretty(&report) {
+                    Ok(json) => print(&json),
+                    Err(err) => eprintln!("Error: {err}"),
+                }
+            }
+        }
+    }
+}
+
+pub fn execute_and_print(
+    run: Run,
+    format: Format,
+    report_progress: Box<dyn ReportProgress + Send>,
+    progress_on_stdout: bool,
+) {
+    execute_timed(run, format, report_progress).print(format, progress_on_stdout)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::framework::{report, NoOpReportProgress};
+
+    use super::*;
+
+    #[test]
+    fn timeout_cancels_run() {
+        let cancellation = CancellationToken::default();
+        let run = Run {
+            day: "day00",
+            part: "part_one",
+            thread_func: Box::new(|_, report_progress| {
+                while !report_progress.is_cancelled() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Ok("cancelled".to_string())
+            }),
+            input: String::new(),
+            expected: None,
+            cancellation: cancellation.clone(),
+            stamper: Stamper::default(),
+            timeout: Some(Duration::from_millis(20)),
+        };
+        let err = execute(run, Box::new(NoOpReportProgress)).unwrap_err();
+        assert!(err.to_string().starts_with("Timed out"));
+        assert!(cancellation.is_cancelled());
+    }
+
+    #[test]
+    fn json_counts_events_nobody_listens_for() {
+        let run = |format| {
+            let run = Run {
+                day: "day00",
+                part: "part_one",
+                thread_func: Box::new(|_, report_progress| {
+                    report!(report_progress, 1);
+                    report!(report_progress, 2);
+                    Ok("done".to_string())
+                }),
+                input: String::new(),
+                expected: None,
+                cancellation: CancellationToken::default(),
+                stamper: Stamper::default(),
+                timeout: None,
+            };
+            execute_timed(run, format, Box::new(NoOpReportProgress))
+        };
+        assert_eq!(run(Format::Json).progress_events, 2);
+        assert_eq!(run(Format::Text).progress_events, 0);
+    }
+}
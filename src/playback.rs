@@ -0,0 +1,176 @@
+use std::ops::Range;
+use std::time::Duration;
+
+const MIN_RATE: f64 = 1.0;
+/// Speeding up past this switches to playing events as soon as they arrive
+const MAX_RATE: f64 = 4096.0;
+
+/// Decides how many of the received progress events have been handed to the draw runtime
+pub struct Playback {
+    pub paused: bool,
+    /// Events per second; `None` plays events as soon as they arrive
+    pub rate: Option<f64>,
+    position: usize,
+    /// Fractional events carried over between frames
+    budget: f64,
+    pending_steps: usize,
+    seek_target: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Advance {
+    /// Apply these events on top of the current state
+    Apply(Range<usize>),
+    /// Restart the draw runtime and apply this many events from the start
+    Rebuild(usize),
+}
+
+impl Playback {
+    pub fn new(rate: Option<f64>) -> Self {
+        Playback {
+            paused: false,
+            rate,
+            position: 0,
+            budget: 0.0,
+            pending_steps: 0,
+            seek_target: None,
+        }
+    }
+
+    /// Number of events that have been applied
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Pauses and moves forward by a single event
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.pending_steps += 1;
+    }
+
+    /// Pauses and moves back by a single event
+    pub fn step_back(&mut self) {
+        self.paused = true;
+        self.seek(self.position.saturating_sub(1));
+    }
+
+    pub fn seek(&mut self, position: usize) {
+        self.seek_target = Some(position);
+    }
+
+    /// Moves back to `position` when not every event from `advance` could be applied in time;
+    /// the rest are applied first thing next frame
+    pub fn applied_until(&mut self, position: usize) {
+        self.pending_steps += self.position.saturating_sub(position);
+        self.position = self.position.min(position);
+    }
+
+    pub fn faster(&mut self) {
+        self.rate = self
+            .rate
+            .map(|rate| rate * 2.0)
+            .filter(|&rate| rate <= MAX_RATE);
+    }
+
+    pub fn slower(&mut self) {
+        self.rate = Some(
+            self.rate
+                .map_or(MAX_RATE, |rate| (rate / 2.0).max(MIN_RATE)),
+        );
+    }
+
+    pub fn advance(&mut self, elapsed: Duration, available: usize) -> Advance {
+        if let Some(target) = self.seek_target.take() {
+            let target = target.min(available);
+            self.budget = 0.0;
+            self.pending_steps = 0;
+            let previous = self.position;
+            self.position = target;
+            return if target < previous {
+                Advance::Rebuild(target)
+            } else {
+                Advance::Apply(previous..target)
+            };
+        }
+
+        let mut count = std::mem::take(&mut self.pending_steps);
+        if !self.paused {
+            match self.rate {
+                None => count = available,
+                Some(rate) => {
+                    self.budget += rate * elapsed.as_secs_f64();
+                    let whole = self.budget.floor();
+                    self.budget -= whole;
+                    count += whole as usize;
+                }
+            }
+        }
+        let end = self.position.saturating_add(count).min(available);
+        if end == available {
+            // don't bank time while waiting on the solver
+            self.budget = 0.0;
+        }
+        let range = self.position..end;
+        self.position = end;
+        Advance::Apply(range)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn rate_limits_events() {
+        let mut playback = Playback::new(Some(25.0));
+        assert_eq!(playback.advance(FRAME, 100), Advance::Apply(0..2));
+        assert_eq!(playback.advance(FRAME, 100), Advance::Apply(2..5));
+        assert_eq!(playback.advance(FRAME, 6), Advance::Apply(5..6));
+    }
+
+    #[test]
+    fn unlimited_rate_plays_everything() {
+        let mut playback = Playback::new(None);
+        assert_eq!(playback.advance(FRAME, 10), Advance::Apply(0..10));
+        assert_eq!(playback.advance(FRAME, 12), Advance::Apply(10..12));
+    }
+
+    #[test]
+    fn step_and_pause() {
+        let mut playback = Playback::new(None);
+        playback.step();
+        assert_eq!(playback.advance(FRAME, 10), Advance::Apply(0..1));
+        assert_eq!(playback.advance(FRAME, 10), Advance::Apply(1..1));
+        playback.toggle_pause();
+        assert_eq!(playback.advance(FRAME, 10), Advance::Apply(1..10));
+    }
+
+    #[test]
+    fn unapplied_events_carry_over() {
+        let mut playback = Playback::new(Some(10.0));
+        playback.seek(8);
+        assert_eq!(playback.advance(FRAME, 10), Advance::Apply(0..8));
+        playback.applied_until(3);
+        assert_eq!(playback.position(), 3);
+        assert_eq!(playback.advance(FRAME, 10), Advance::Apply(3..9));
+    }
+
+    #[test]
+    fn seek_backwards_rebuilds() {
+        let mut playback = Playback::new(None);
+        playback.advance(FRAME, 10);
+        playback.seek(4);
+        assert_eq!(playback.advance(FRAME, 10), Advance::Rebuild(4));
+        playback.paused = true;
+        playback.seek(20);
+        assert_eq!(playback.advance(FRAME, 10), Advance::Apply(4..10));
+        playback.step_back();
+        assert_eq!(playback.advance(FRAME, 10), Advance::Rebuild(9));
+    }
+}
@@ -1,5 +1,4 @@
 pub use anyhow::{anyhow, bail, Error, Result};
-pub use indoc::indoc;
 pub use itertools::Itertools;
 pub use lazy_static::lazy_static;
 pub use tap::prelude::*;
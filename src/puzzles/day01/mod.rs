@@ -5,15 +5,16 @@ use std::str::FromStr;
 use itertools::Itertools;
 
 use crate::prelude::*;
+use crate::registry::register_puzzle;
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct ElfInventory(Vec<i64>);
+pub struct ElfInventory(Vec<i64>);
 impl From<Vec<i64>> for ElfInventory {
     fn from(input: Vec<i64>) -> Self {
         Self(input)
     }
 }
 
-fn parse_input(input: &str) -> Result<Vec<ElfInventory>> {
+pub fn parse_input(input: &str) -> Result<Vec<ElfInventory>> {
     let lines = input.lines().collect_vec();
     let groups = lines
         .split(|it| it.trim().is_empty())
@@ -43,40 +44,33 @@ fn find_top_three(inventories: &[ElfInventory]) -> Vec<i64> {
     calories_per_elf.into_iter().take(3).collect_vec()
 }
 
-pub fn part_one() -> Result<i64> {
-    let inventories = parse_input(include_str!("./puzzle_input.txt"))?;
-    let result = find_max_inventory(&inventories);
+pub fn part_one(inventories: &[ElfInventory]) -> Result<i64> {
+    let result = find_max_inventory(inventories);
     result.ok_or_else(|| anyhow!("No result found"))
 }
 
-pub fn part_two() -> Result<i64> {
-    let inventories = parse_input(include_str!("./puzzle_input.txt"))?;
-    let result: i64 = find_top_three(&inventories).iter().sum();
-    return Ok(result);
+pub fn part_two(inventories: &[ElfInventory]) -> Result<i64> {
+    let result: i64 = find_top_three(inventories).iter().sum();
+    Ok(result)
+}
+
+register_puzzle! {
+    title: "Calorie Counting",
+    input: Vec<ElfInventory>,
+    parse: parse_input,
+    parts: [part_one, part_two],
+    samples: [
+        sample_input { part_one: "24000", part_two: "45000" },
+    ],
 }
 
 #[cfg(test)]
 mod test {
-    use indoc::indoc;
+    use crate::load_algorithm::load_input;
 
     use super::*;
 
-    const SAMPLE_INPUT: &str = indoc! {"
-        1000
-        2000
-        3000
-
-        4000
-
-        5000
-        6000
-
-        7000
-        8000
-        9000
-
-        10000
-    "};
+    const SAMPLE_INPUT: &str = include_str!("./sample_input.txt");
 
     #[test]
     fn max_inventory() {
@@ -86,8 +80,10 @@ mod test {
     }
 
     #[test]
+    #[ignore = "requires inputs/day01.txt"]
     fn part_one_answer() {
-        let result = part_one();
+        let input = parse_input(&load_input("day01", None).unwrap()).unwrap();
+        let result = part_one(&input);
         assert_eq!(result.unwrap(), 72511);
     }
 
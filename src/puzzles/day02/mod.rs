@@ -6,11 +6,12 @@ use chumsky::prelude::*;
 use serde::Serialize;
 
 use crate::prelude::*;
+use crate::registry::register_puzzle;
 
 #[derive(Debug, Serialize, Clone)]
 struct Entry(char, char);
 #[derive(Debug, Serialize, Clone)]
-struct Strategy(Vec<Entry>);
+pub struct Strategy(Vec<Entry>);
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum RPSMove {
@@ -75,7 +76,7 @@ fn round_score(your_move: RPSMove, opponent_move: RPSMove) -> i64 {
         Winner::Left => 6,
     };
 
-    return base_score + outcome_score;
+    base_score + outcome_score
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -123,9 +124,12 @@ fn get_total_score_for_strategy(strategy: &Strategy) -> Result<i64> {
     Ok(outcomes?.into_iter().sum())
 }
 
-pub fn part_one() -> Result<i64> {
-    let strategy = include_str!("./puzzle_input.txt").parse()?;
-    get_total_score_for_strategy(&strategy)
+pub fn parse_input(input: &str) -> Result<Strategy> {
+    input.parse()
+}
+
+pub fn part_one(strategy: &Strategy) -> Result<i64> {
+    get_total_score_for_strategy(strategy)
 }
 
 fn get_total_score_for_smarter_strategy(strategy: &Strategy) -> Result<i64> {
@@ -154,20 +158,27 @@ fn get_total_score_for_smarter_strategy(strategy: &Strategy) -> Result<i64> {
     Ok(outcomes?.into_iter().sum())
 }
 
-pub fn part_two() -> Result<i64> {
-    let strategy = include_str!("./puzzle_input.txt").parse()?;
-    get_total_score_for_smarter_strategy(&strategy)
+pub fn part_two(strategy: &Strategy) -> Result<i64> {
+    get_total_score_for_smarter_strategy(strategy)
+}
+
+register_puzzle! {
+    title: "Rock Paper Scissors",
+    input: Strategy,
+    parse: parse_input,
+    parts: [part_one, part_two],
+    samples: [
+        sample_input { part_one: "15", part_two: "12" },
+    ],
 }
 
 #[cfg(test)]
 mod test {
+    use crate::load_algorithm::load_input;
+
     use super::*;
 
-    const SAMPLE_INPUT: &str = indoc! {"
-      A Y
-      B X
-      C Z
-    "};
+    const SAMPLE_INPUT: &str = include_str!("./sample_input.txt");
 
     #[test]
     fn test_parse() {
@@ -184,8 +195,10 @@ mod test {
     }
 
     #[test]
+    #[ignore = "requires inputs/day02.txt"]
     fn part_one_answer() {
-        insta::assert_display_snapshot!(part_one().unwrap(), @"15422");
+        let input = parse_input(&load_input("day02", None).unwrap()).unwrap();
+        insta::assert_display_snapshot!(part_one(&input).unwrap(), @"15422");
     }
 
     #[test]
@@ -196,7 +209,9 @@ mod test {
     }
 
     #[test]
+    #[ignore = "requires inputs/day02.txt"]
     fn part_two_answer() {
-        assert_eq!(part_two().unwrap(), 15442);
+        let input = parse_input(&load_input("day02", None).unwrap()).unwrap();
+        assert_eq!(part_two(&input).unwrap(), 15442);
     }
 }
@@ -5,6 +5,7 @@ use std::fmt::Debug;
 use std::str::FromStr;
 
 use crate::prelude::*;
+use crate::registry::register_puzzle;
 
 #[derive(Clone)]
 struct Rucksack(Vec<char>, Vec<char>);
@@ -27,7 +28,7 @@ impl Rucksack {
 }
 
 #[derive(Debug, Clone)]
-struct Input(Vec<Rucksack>);
+pub struct Input(Vec<Rucksack>);
 
 impl FromStr for Input {
     type Err = anyhow::Error;
@@ -46,18 +47,18 @@ impl FromStr for Input {
             })
             .collect::<Result<Vec<Rucksack>>>();
 
-        rucksacks.map(|it| Input(it))
+        rucksacks.map(Input)
     }
 }
 
 fn get_repeated_item(rucksack: &Rucksack) -> Option<char> {
     let second_compartment_set: HashSet<char> = rucksack.1.iter().copied().collect();
     for c in rucksack.0.iter() {
-        if second_compartment_set.contains(&c) {
+        if second_compartment_set.contains(c) {
             return Some(*c);
         }
     }
-    return None;
+    None
 }
 
 fn get_priority(item: char) -> Result<i64> {
@@ -75,7 +76,7 @@ fn find_badges(input: &Input) -> Result<i64> {
         .map(|group| {
             let sets = group
                 .iter()
-                .map(|rucksack| HashSet::<_>::from_iter(rucksack.all_items().into_iter()));
+                .map(|rucksack| HashSet::<_>::from_iter(rucksack.all_items()));
             let combined_set = sets
                 .reduce(|prev, next| prev.intersection(&next).copied().collect())
                 .unwrap();
@@ -90,16 +91,18 @@ fn find_badges(input: &Input) -> Result<i64> {
     Ok(all_badges?.iter().sum())
 }
 
-pub fn part_one() -> Result<i64> {
-    let parsed: Input = include_str!("./puzzle_input.txt").parse()?;
-    let result: i64 = parsed
+pub fn parse_input(input: &str) -> Result<Input> {
+    input.parse()
+}
+
+pub fn part_one(input: &Input) -> Result<i64> {
+    let result: i64 = input
         .0
         .iter()
         .map(|it| {
-            get_repeated_item(&it)
-                .ok_or_else(|| anyhow!("couldn't find a repeated item for {it:?}"))
+            get_repeated_item(it).ok_or_else(|| anyhow!("couldn't find a repeated item for {it:?}"))
         })
-        .map_ok(|it| get_priority(it))
+        .map_ok(get_priority)
         .flatten_ok()
         .collect::<Result<Vec<i64>>>()?
         .iter()
@@ -107,22 +110,37 @@ pub fn part_one() -> Result<i64> {
     Ok(result)
 }
 
-pub fn part_two() -> Result<i64> {
-    let parsed: Input = include_str!("./puzzle_input.txt").parse()?;
-    find_badges(&parsed)
+pub fn part_two(input: &Input) -> Result<i64> {
+    find_badges(input)
+}
+
+register_puzzle! {
+    title: "Rucksack Reorganization",
+    input: Input,
+    parse: parse_input,
+    parts: [part_one, part_two],
+    samples: [
+        sample_input { part_one: "157", part_two: "70" },
+    ],
 }
 
 #[cfg(test)]
 mod test {
+    use crate::load_algorithm::load_input;
+
     use super::*;
 
     #[test]
+    #[ignore = "requires inputs/day03.txt"]
     fn part_one_answer() {
-        assert_eq!(part_one().unwrap(), 8243);
+        let input = parse_input(&load_input("day03", None).unwrap()).unwrap();
+        assert_eq!(part_one(&input).unwrap(), 8243);
     }
 
     #[test]
+    #[ignore = "requires inputs/day03.txt"]
     fn part_two_answer() {
-        assert_eq!(part_two().unwrap(), 2631);
+        let input = parse_input(&load_input("day03", None).unwrap()).unwrap();
+        assert_eq!(part_two(&input).unwrap(), 2631);
     }
 }
@@ -5,32 +5,46 @@ use std::str::FromStr;
 use chumsky::prelude::*;
 use serde::Serialize;
 
-use crate::{framework::ReportProgress, prelude::*};
+use crate::{
+    framework::{report, Level, ReportProgress},
+    prelude::*,
+    registry::register_puzzle,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
-struct Assignment {
+pub struct Assignment {
     start: i64,
     end: i64,
 }
 
 impl Assignment {
-    fn contains(&self, other: &Self, report_progress: &impl ReportProgress) -> bool {
+    fn contains(&self, other: &Self, report_progress: &impl ReportProgress<ProgressEvent>) -> bool {
         self.contains_point(other.start, report_progress)
             && self.contains_point(other.end, report_progress)
     }
 
-    fn overlaps_with(&self, other: &Self, report_progress: &impl ReportProgress) -> bool {
+    fn overlaps_with(
+        &self,
+        other: &Self,
+        report_progress: &impl ReportProgress<ProgressEvent>,
+    ) -> bool {
         self.contains_point(other.start, report_progress)
             || self.contains_point(other.end, report_progress)
             || other.contains_point(self.start, report_progress)
             || other.contains_point(self.end, report_progress)
     }
 
-    fn contains_point(&self, point: i64, report_progress: &impl ReportProgress) -> bool {
+    fn contains_point(
+        &self,
+        point: i64,
+        report_progress: &impl ReportProgress<ProgressEvent>,
+    ) -> bool {
         if point >= self.start && point <= self.end {
-            report_progress.report_progress(Box::new(ProgressEvent::IntersectionFound {
-                position: point,
-            }));
+            report!(
+                report_progress,
+                Level::Debug,
+                ProgressEvent::IntersectionFound { position: point }
+            );
             true
         } else {
             false
@@ -39,19 +53,19 @@ impl Assignment {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
-struct Pair(Assignment, Assignment);
+pub struct Pair(Assignment, Assignment);
 
 impl Pair {
-    fn has_overlap(&self, report_progress: &impl ReportProgress) -> bool {
+    fn has_overlap(&self, report_progress: &impl ReportProgress<ProgressEvent>) -> bool {
         self.0.overlaps_with(&self.1, report_progress)
     }
 
-    fn has_full_overlap(&self, report_progress: &impl ReportProgress) -> bool {
+    fn has_full_overlap(&self, report_progress: &impl ReportProgress<ProgressEvent>) -> bool {
         if self.0.contains(&self.1, report_progress) {
-            report_progress.report_progress(Box::new(ProgressEvent::ContainsOther { which: 1 }));
+            report!(report_progress, ProgressEvent::ContainsOther { which: 1 });
             true
         } else if self.1.contains(&self.0, report_progress) {
-            report_progress.report_progress(Box::new(ProgressEvent::ContainsOther { which: 0 }));
+            report!(report_progress, ProgressEvent::ContainsOther { which: 0 });
             true
         } else {
             false
@@ -60,7 +74,7 @@ impl Pair {
 }
 
 #[derive(Debug, Clone)]
-struct Input(Vec<Pair>);
+pub struct Input(Vec<Pair>);
 
 impl FromStr for Input {
     type Err = anyhow::Error;
@@ -84,10 +98,7 @@ impl FromStr for Input {
                 .map(|(left, right)| Pair(left, right))
         };
 
-        let parser = pair()
-            .separated_by(text::newline())
-            .padded()
-            .map(|pairs| Input(pairs));
+        let parser = pair().separated_by(text::newline()).padded().map(Input);
 
         parser.parse(s).map_err(|err| {
             err.iter()
@@ -98,65 +109,137 @@ impl FromStr for Input {
     }
 }
 
-#[derive(Debug, Serialize)]
-enum ProgressEvent {
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub enum ProgressEvent {
     AnalyzePair(Pair),
     IntersectionFound { position: i64 },
     ContainsOther { which: u8 },
 }
 
-pub fn part_one(report_progress: &impl ReportProgress) -> Result<usize> {
-    let input: Input = include_str!("./puzzle_input.txt").parse()?;
-    let overlaps = input
-        .0
-        .iter()
-        .filter(|pair| {
-            report_progress.report_progress(Box::new(ProgressEvent::AnalyzePair(**pair)));
-            pair.has_full_overlap(report_progress)
-        })
-        .count();
+pub fn parse_input(input: &str) -> Result<Input> {
+    input.parse()
+}
+
+pub fn part_one(
+    input: &Input,
+    report_progress: &impl ReportProgress<ProgressEvent>,
+) -> Result<usize> {
+    let total = input.0.len() as u64;
+    let mut overlaps = 0;
+    for (index, pair) in input.0.iter().enumerate() {
+        report_progress.check_cancelled()?;
+        report!(report_progress, ProgressEvent::AnalyzePair(*pair));
+        report_progress.report_counter("pairs analysed", 1);
+        if pair.has_full_overlap(report_progress) {
+            overlaps += 1;
+        }
+        report_progress.report_fraction(index as u64 + 1, total);
+    }
     Ok(overlaps)
 }
 
-pub fn part_two(report_progress: &impl ReportProgress) -> Result<usize> {
-    let input: Input = include_str!("./puzzle_input.txt").parse()?;
-    let overlaps = input
-        .0
-        .iter()
-        .filter(|pair| {
-            report_progress.report_progress(Box::new(ProgressEvent::AnalyzePair(**pair)));
-            pair.has_overlap(report_progress)
-        })
-        .count();
+pub fn part_two(
+    input: &Input,
+    report_progress: &impl ReportProgress<ProgressEvent>,
+) -> Result<usize> {
+    let total = input.0.len() as u64;
+    let mut overlaps = 0;
+    for (index, pair) in input.0.iter().enumerate() {
+        report_progress.check_cancelled()?;
+        report!(report_progress, ProgressEvent::AnalyzePair(*pair));
+        report_progress.report_counter("pairs analysed", 1);
+        if pair.has_overlap(report_progress) {
+            overlaps += 1;
+        }
+        report_progress.report_fraction(index as u64 + 1, total);
+    }
     Ok(overlaps)
 }
 
+register_puzzle! {
+    title: "Camp Cleanup",
+    input: Input,
+    events: ProgressEvent,
+    parse: parse_input,
+    parts: [part_one(report_progress), part_two(report_progress)],
+    samples: [
+        sample_input { part_one: "2", part_two: "4" },
+    ],
+}
+
 #[cfg(test)]
 mod test {
-    use crate::framework::NoOpReportProgress;
+    use std::sync::Mutex;
+
+    use crate::framework::{
+        CancellableReportProgress, CancellationToken, Cancelled, ErasingReportProgress,
+        NoOpReportProgress,
+    };
+    use crate::load_algorithm::load_input;
 
     use super::*;
 
     #[test]
+    #[ignore = "requires inputs/day04.txt"]
     fn part_one_answer() {
-        let report_progress: Box<dyn ReportProgress> = Box::new(NoOpReportProgress);
-        assert_eq!(part_one(&report_progress).unwrap(), 305);
+        let input = parse_input(&load_input("day04", None).unwrap()).unwrap();
+        let report_progress = NoOpReportProgress;
+        assert_eq!(part_one(&input, &report_progress).unwrap(), 305);
     }
 
     #[test]
+    #[ignore = "requires inputs/day04.txt"]
     fn part_two_answer() {
-        let report_progress: Box<dyn ReportProgress> = Box::new(NoOpReportProgress);
-        let result = part_two(&report_progress).unwrap();
+        let input = parse_input(&load_input("day04", None).unwrap()).unwrap();
+        let report_progress = NoOpReportProgress;
+        let result = part_two(&input, &report_progress).unwrap();
         assert_eq!(result, 811);
     }
 
     #[test]
     fn troublesome_overlap() {
-        let report_progress: Box<dyn ReportProgress> = Box::new(NoOpReportProgress);
+        let report_progress = NoOpReportProgress;
         let pair = Pair(
             Assignment { start: 6, end: 21 },
             Assignment { start: 5, end: 26 },
         );
         assert!(pair.has_overlap(&report_progress));
     }
+
+    #[test]
+    fn reports_typed_events() {
+        let input = parse_input("2-8,3-7\n6-6,4-6").unwrap();
+        let events = Mutex::new(Vec::new());
+        assert_eq!(part_one(&input, &events).unwrap(), 2);
+        let events = events.into_inner().unwrap();
+        assert_eq!(events[0], ProgressEvent::AnalyzePair(input.0[0]));
+        assert_eq!(
+            events
+                .iter()
+                .filter(|it| matches!(it, ProgressEvent::ContainsOther { .. }))
+                .collect_vec(),
+            [
+                &ProgressEvent::ContainsOther { which: 1 },
+                &ProgressEvent::ContainsOther { which: 0 }
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_once_cancelled() {
+        let input = parse_input("2-8,3-7\n6-6,4-6").unwrap();
+        let cancellation = CancellationToken::default();
+        cancellation.cancel();
+        let report_progress = CancellableReportProgress {
+            inner: Box::new(NoOpReportProgress),
+            cancellation,
+        };
+        let report_progress = ErasingReportProgress::new(&report_progress);
+        for result in [
+            part_one(&input, &report_progress),
+            part_two(&input, &report_progress),
+        ] {
+            assert!(result.unwrap_err().is::<Cancelled>());
+        }
+    }
 }
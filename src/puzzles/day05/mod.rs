@@ -5,6 +5,7 @@ use std::{collections::VecDeque, str::FromStr};
 use chumsky::prelude::*;
 
 use crate::prelude::*;
+use crate::registry::register_puzzle;
 
 #[derive(Debug, Default, PartialEq, Eq, Clone)]
 struct Stack(VecDeque<char>);
@@ -57,6 +58,15 @@ impl StackCollection {
         }
         Ok(())
     }
+
+    fn top_crates(&self) -> String {
+        let top_crates = self
+            .0
+            .iter()
+            .filter_map(|stack| stack.0.back().map(|c| c.to_string()))
+            .collect_vec();
+        top_crates.join("")
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -67,30 +77,22 @@ struct Instruction {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
-struct Input {
+pub struct Input {
     stacks: StackCollection,
     instructions: Vec<Instruction>,
 }
 
 impl Input {
-    fn crates_after_instructions_compat(mut self) -> String {
-        self.stacks.follow_instructions_compat(&self.instructions);
-        self.top_crates()
-    }
-
-    fn top_crates(&self) -> String {
-        let top_crates = self
-            .stacks
-            .0
-            .iter()
-            .filter_map(|stack| stack.0.back().map(|c| c.to_string()))
-            .collect_vec();
-        top_crates.join("")
+    fn crates_after_instructions_compat(&self) -> String {
+        let mut stacks = self.stacks.clone();
+        stacks.follow_instructions_compat(&self.instructions);
+        stacks.top_crates()
     }
 
-    fn crates_after_instructions(mut self) -> Result<String> {
-        self.stacks.follow_instructions(&self.instructions)?;
-        Ok(self.top_crates())
+    fn crates_after_instructions(&self) -> Result<String> {
+        let mut stacks = self.stacks.clone();
+        stacks.follow_instructions(&self.instructions)?;
+        Ok(stacks.top_crates())
     }
 }
 
@@ -98,7 +100,7 @@ impl FromStr for Input {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let digit = || filter(|c: &char| c.is_digit(10)).map(|c| c.to_digit(10).unwrap());
+        let digit = || filter(|c: &char| c.is_ascii_digit()).map(|c| c.to_digit(10).unwrap());
         let inline_whitespace = || just(' ').repeated().ignored();
         let crate_parser = {
             just::<_, _, Simple<_>>('[')
@@ -176,31 +178,35 @@ impl FromStr for Input {
     }
 }
 
-pub fn part_one() -> Result<String> {
-    let input = include_str!("./puzzle_input.txt").parse::<Input>()?;
+pub fn parse_input(input: &str) -> Result<Input> {
+    input.parse()
+}
+
+pub fn part_one(input: &Input) -> Result<String> {
     Ok(input.crates_after_instructions_compat())
 }
 
-pub fn part_two() -> Result<String> {
-    let input = include_str!("./puzzle_input.txt").parse::<Input>()?;
+pub fn part_two(input: &Input) -> Result<String> {
     input.crates_after_instructions()
 }
 
+register_puzzle! {
+    title: "Supply Stacks",
+    input: Input,
+    parse: parse_input,
+    parts: [part_one, part_two],
+    samples: [
+        sample_input { part_one: "CMZ", part_two: "MCD" },
+    ],
+}
+
 #[cfg(test)]
 mod test {
-    use super::*;
+    use crate::load_algorithm::load_input;
 
-    const SAMPLE_INPUT: &str = indoc! {"
-            [D]    
-        [N] [C]    
-        [Z] [M] [P]
-         1   2   3 
+    use super::*;
 
-        move 1 from 2 to 1
-        move 3 from 1 to 3
-        move 2 from 2 to 1
-        move 1 from 1 to 2
-    "};
+    const SAMPLE_INPUT: &str = include_str!("./sample_input.txt");
 
     #[test]
     fn sample_crates_after_instructions() {
@@ -209,8 +215,10 @@ mod test {
     }
 
     #[test]
+    #[ignore = "requires inputs/day05.txt"]
     fn part_one_answer() {
-        assert_eq!(part_one().unwrap(), "FWNSHLDNZ");
+        let input = parse_input(&load_input("day05", None).unwrap()).unwrap();
+        assert_eq!(part_one(&input).unwrap(), "FWNSHLDNZ");
     }
 
     #[test]
@@ -220,7 +228,9 @@ mod test {
     }
 
     #[test]
+    #[ignore = "requires inputs/day05.txt"]
     fn part_two_answer() {
-        assert_eq!(part_two().unwrap(), "RNRGDNFQG");
+        let input = parse_input(&load_input("day05", None).unwrap()).unwrap();
+        assert_eq!(part_two(&input).unwrap(), "RNRGDNFQG");
     }
 }
@@ -1,23 +1,27 @@
 // Day 6: Tuning Trouble
 
 use crate::prelude::*;
+use crate::registry::register_puzzle;
 
-pub fn part_one() -> Result<usize> {
-    first_new_packet_marker(include_str!("./puzzle_input.txt"))
-        .ok_or_else(|| anyhow!("No start-of-packet marker detected."))
+pub struct Input(Vec<char>);
+
+pub fn parse_input(input: &str) -> Result<Input> {
+    Ok(Input(input.chars().collect()))
+}
+
+pub fn part_one(input: &Input) -> Result<usize> {
+    first_new_packet_marker(&input.0).ok_or_else(|| anyhow!("No start-of-packet marker detected."))
 }
 
-pub fn part_two() -> Result<usize> {
-    first_unique_slice(include_str!("./puzzle_input.txt"), 14)
-        .ok_or_else(|| anyhow!("No start-of-message marker detected."))
+pub fn part_two(input: &Input) -> Result<usize> {
+    first_unique_slice(&input.0, 14).ok_or_else(|| anyhow!("No start-of-message marker detected."))
 }
 
-fn first_new_packet_marker(input: &str) -> Option<usize> {
-    first_unique_slice(input, 4)
+fn first_new_packet_marker(char_list: &[char]) -> Option<usize> {
+    first_unique_slice(char_list, 4)
 }
 
-fn first_unique_slice(input: &str, slice_length: usize) -> Option<usize> {
-    let char_list: Vec<char> = input.chars().collect();
+fn first_unique_slice(char_list: &[char], slice_length: usize) -> Option<usize> {
     let previous_slice_length = slice_length - 1;
     let mut last_duplicate: Option<usize> = None;
     for (i, current_char) in char_list.iter().enumerate() {
@@ -44,41 +48,61 @@ fn first_unique_slice(input: &str, slice_length: usize) -> Option<usize> {
     None
 }
 
+register_puzzle! {
+    title: "Tuning Trouble",
+    input: Input,
+    parse: parse_input,
+    parts: [part_one, part_two],
+    samples: [
+        sample_input { part_one: "7", part_two: "19" },
+        sample_input_2 { part_one: "5", part_two: "23" },
+        sample_input_3 { part_one: "6", part_two: "23" },
+        sample_input_4 { part_one: "10", part_two: "29" },
+        sample_input_5 { part_one: "11", part_two: "26" },
+    ],
+}
+
 #[cfg(test)]
 mod test {
+    use crate::load_algorithm::load_input;
+
     use super::*;
 
     #[test]
     fn part_one_examples() {
         assert_eq!(
-            first_new_packet_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb").unwrap(),
+            part_one(&parse_input("mjqjpqmgbljsphdztnvjfqwrcgsmlb").unwrap()).unwrap(),
             7
         );
         assert_eq!(
-            first_new_packet_marker("bvwbjplbgvbhsrlpgdmjqwftvncz").unwrap(),
+            part_one(&parse_input("bvwbjplbgvbhsrlpgdmjqwftvncz").unwrap()).unwrap(),
             5
         );
         assert_eq!(
-            first_new_packet_marker("nppdvjthqldpwncqszvftbrmjlhg").unwrap(),
+            part_one(&parse_input("nppdvjthqldpwncqszvftbrmjlhg").unwrap()).unwrap(),
             6
         );
         assert_eq!(
-            first_new_packet_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").unwrap(),
+            part_one(&parse_input("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").unwrap()).unwrap(),
             10
         );
         assert_eq!(
-            first_new_packet_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw").unwrap(),
+            part_one(&parse_input("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw").unwrap()).unwrap(),
             11
         );
     }
 
     #[test]
+    #[ignore = "requires inputs/day06.txt"]
     fn part_one_answer() {
-        assert_eq!(part_one().unwrap(), 1093);
+        let input = parse_input(&load_input("day06", None).unwrap()).unwrap();
+        assert_eq!(part_one(&input).unwrap(), 1093);
     }
 
     #[test]
+    #[ignore = "requires inputs/day06.txt"]
     fn part_two_answer() {
-        assert_eq!(part_two().unwrap(), 3534);
+        let input = parse_input(&load_input("day06", None).unwrap()).unwrap();
+        assert_eq!(part_two(&input).unwrap(), 3534);
     }
 }
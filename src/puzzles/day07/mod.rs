@@ -3,9 +3,10 @@
 use std::collections::HashMap;
 
 use crate::prelude::*;
+use crate::registry::register_puzzle;
 
 #[derive(Clone, Debug, Default)]
-struct Directory {
+pub struct Directory {
     items: HashMap<String, Item>,
 }
 
@@ -58,26 +59,24 @@ impl Directory {
                 current_path = vec![];
             } else if command == "$ cd .." {
                 current_path.pop();
-            } else if command.starts_with("$ cd ") {
-                let dirname = &command["$ cd ".len()..];
+            } else if let Some(dirname) = command.strip_prefix("$ cd ") {
                 current_path.push(dirname.to_string());
             } else if command == "$ ls" {
                 // no-op
-            } else if command.starts_with("dir ") {
+            } else if let Some(dirname) = command.strip_prefix("dir ") {
                 // let
-                let dirname = &command["dir ".len()..];
                 current_dir_items
                     .insert(dirname.to_string(), Item::Directory(Directory::default()));
             } else if command
                 .chars()
                 .next()
-                .map(|first_char| first_char.is_digit(10))
+                .map(|first_char| first_char.is_ascii_digit())
                 .unwrap_or(false)
             {
                 let (size, name) = command.split_at(command.find(" ").ok_or_else(|| {
                     anyhow!("expected \" \" separator between filesize and name in \"{command}\"")
                 })?);
-                let size = u64::from_str_radix(size, 10)?;
+                let size = size.parse::<u64>()?;
                 let name = name[1..].to_string(); // split_at() leaves a leading space
                 current_dir_items.insert(name, Item::File { size });
             } else {
@@ -129,7 +128,7 @@ const TOTAL_SPACE: u64 = 70_000_000;
 const REQUIRED_SPACE: u64 = 30_000_000;
 fn clear_space(fs: &Directory) -> Option<u64> {
     let mut size_cache: HashMap<Vec<String>, u64> = HashMap::new();
-    let current_size = fs.get_size_with_cache(&vec![], &mut size_cache);
+    let current_size = fs.get_size_with_cache(&[], &mut size_cache);
     let unused_space = TOTAL_SPACE - current_size;
     let amount_to_remove = REQUIRED_SPACE - unused_space;
     fs.dirs_recursive(vec![])
@@ -138,27 +137,45 @@ fn clear_space(fs: &Directory) -> Option<u64> {
         .min()
 }
 
-pub fn part_one() -> Result<u64> {
-    let fs = Directory::fs_from_input(include_str!("./puzzle_input.txt"))?;
-    Ok(crawl_for_small_dirs(&fs))
+pub fn parse_input(input: &str) -> Result<Directory> {
+    Directory::fs_from_input(input)
 }
 
-pub fn part_two() -> Result<u64> {
-    let fs = Directory::fs_from_input(include_str!("./puzzle_input.txt"))?;
-    clear_space(&fs).ok_or_else(|| anyhow!("Couldn't find a suitable directory"))
+pub fn part_one(fs: &Directory) -> Result<u64> {
+    Ok(crawl_for_small_dirs(fs))
+}
+
+pub fn part_two(fs: &Directory) -> Result<u64> {
+    clear_space(fs).ok_or_else(|| anyhow!("Couldn't find a suitable directory"))
+}
+
+register_puzzle! {
+    title: "No Space Left On Device",
+    input: Directory,
+    parse: parse_input,
+    parts: [part_one, part_two],
+    samples: [
+        sample_input { part_one: "95437", part_two: "24933642" },
+    ],
 }
 
 #[cfg(test)]
 mod test {
+    use crate::load_algorithm::load_input;
+
     use super::*;
 
     #[test]
+    #[ignore = "requires inputs/day07.txt"]
     fn part_one_answer() {
-        assert_eq!(part_one().unwrap(), 1077191);
+        let input = parse_input(&load_input("day07", None).unwrap()).unwrap();
+        assert_eq!(part_one(&input).unwrap(), 1077191);
     }
 
     #[test]
+    #[ignore = "requires inputs/day07.txt"]
     fn part_two_answer() {
-        assert_eq!(part_two().unwrap(), 5649896);
+        let input = parse_input(&load_input("day07", None).unwrap()).unwrap();
+        assert_eq!(part_two(&input).unwrap(), 5649896);
     }
 }
@@ -1,9 +1,22 @@
-pub mod day01;
-pub mod day02;
-pub mod day03;
-pub mod day04;
-pub mod day05;
-pub mod day06;
-pub mod day07;
+use crate::registry::RegisteredPuzzle;
 
-pub mod test_algo;
+macro_rules! puzzles {
+    ($($day:ident),* $(,)?) => {
+        $(pub mod $day;)*
+
+        pub fn registry() -> Vec<RegisteredPuzzle> {
+            vec![$(RegisteredPuzzle::new::<$day::Puzzle>(stringify!($day))),*]
+        }
+    };
+}
+
+puzzles! {
+    day01,
+    day02,
+    day03,
+    day04,
+    day05,
+    day06,
+    day07,
+    test_algo,
+}
@@ -1,22 +1,26 @@
 // 2019 Day 1: The Tyranny of the Rocket Equation
 
-use crate::framework::ReportProgress;
+use crate::framework::{report, ReportProgress};
 use crate::prelude::*;
+use crate::registry::register_puzzle;
 use serde::Serialize;
 
-lazy_static! {
-    static ref PUZZLE_INPUT: Vec<i64> = include_str!("./test_input.txt")
+pub fn parse_input(input: &str) -> Result<Vec<i64>> {
+    input
         .lines()
-        .map(|it| it.parse().unwrap())
-        .collect();
+        .map(|it| {
+            it.parse()
+                .map_err(|err| anyhow!("Invalid mass {it:?}: {err}"))
+        })
+        .collect()
 }
 
 pub fn fuel_amount(mass: i64) -> i64 {
     (mass / 3) - 2
 }
 
-pub fn part_one() -> i64 {
-    PUZZLE_INPUT.iter().map(|&num| fuel_amount(num)).sum()
+pub fn part_one(masses: &[i64]) -> Result<i64> {
+    Ok(masses.iter().map(|&num| fuel_amount(num)).sum())
 }
 
 #[derive(Serialize)]
@@ -25,31 +29,52 @@ pub enum PartTwoProgress {
     AdditionalFuel { mass: i64 },
 }
 
-pub fn recursive_fuel_amount(mass: i64, progress: &impl ReportProgress) -> i64 {
+pub fn recursive_fuel_amount(mass: i64, progress: &impl ReportProgress<PartTwoProgress>) -> i64 {
     let required_fuel = fuel_amount(mass);
     if required_fuel > 0 {
-        progress.report_progress(Box::new(PartTwoProgress::AdditionalFuel {
-            mass: required_fuel,
-        }));
+        report!(
+            progress,
+            PartTwoProgress::AdditionalFuel {
+                mass: required_fuel,
+            }
+        );
         let additional_fuel = recursive_fuel_amount(required_fuel, progress);
-        return required_fuel + additional_fuel;
+        required_fuel + additional_fuel
     } else {
-        return 0;
-    };
+        0
+    }
 }
 
-pub fn part_two(progress: &impl ReportProgress) -> i64 {
-    PUZZLE_INPUT
+pub fn part_two(masses: &[i64], progress: &impl ReportProgress<PartTwoProgress>) -> Result<i64> {
+    masses
         .iter()
-        .map(|&num| {
-            progress.report_progress(Box::new(PartTwoProgress::NewPart { mass: num }));
-            recursive_fuel_amount(num, progress)
+        .enumerate()
+        .map(|(index, &num)| {
+            progress.check_cancelled()?;
+            report!(progress, PartTwoProgress::NewPart { mass: num });
+            progress.report_gauge("module mass", num as f64);
+            let fuel = recursive_fuel_amount(num, progress);
+            progress.report_fraction(index as u64 + 1, masses.len() as u64);
+            Ok(fuel)
         })
         .sum()
 }
 
+register_puzzle! {
+    title: "The Tyranny of the Rocket Equation",
+    input: Vec<i64>,
+    events: PartTwoProgress,
+    parse: parse_input,
+    parts: [part_one, part_two(report_progress)],
+    samples: [
+        sample_input { part_one: "34241", part_two: "51316" },
+    ],
+}
+
 #[cfg(test)]
 mod part_one_test {
+    use crate::load_algorithm::load_input;
+
     use super::*;
 
     #[test]
@@ -70,7 +95,8 @@ mod part_one_test {
     }
     #[test]
     fn part_one_test() {
-        let result: i64 = part_one();
+        let input = parse_input(&load_input("test_algo", None).unwrap()).unwrap();
+        let result: i64 = part_one(&input).unwrap();
         assert_eq!(result, 3394106);
     }
 }
@@ -78,20 +104,19 @@ mod part_one_test {
 #[cfg(test)]
 mod part_two_test {
     use crate::framework::NoOpReportProgress;
+    use crate::load_algorithm::load_input;
 
     use super::*;
     #[test]
     fn test_cases() {
-        assert_eq!(recursive_fuel_amount(14, &mut NoOpReportProgress), 2);
-        assert_eq!(recursive_fuel_amount(1969, &mut NoOpReportProgress), 966);
-        assert_eq!(
-            recursive_fuel_amount(100756, &mut NoOpReportProgress),
-            50346
-        );
+        assert_eq!(recursive_fuel_amount(14, &NoOpReportProgress), 2);
+        assert_eq!(recursive_fuel_amount(1969, &NoOpReportProgress), 966);
+        assert_eq!(recursive_fuel_amount(100756, &NoOpReportProgress), 50346);
     }
     #[test]
     fn part_two_test() {
-        let result: i64 = part_two(&mut NoOpReportProgress);
+        let input = parse_input(&load_input("test_algo", None).unwrap()).unwrap();
+        let result: i64 = part_two(&input, &NoOpReportProgress).unwrap();
         assert_eq!(result, 5088280);
     }
 }
@@ -0,0 +1,395 @@
+use std::fs::{self, File};
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use erased_serde::Serialize;
+use serde::Deserialize;
+
+use crate::framework::{
+    Envelope, EventMeta, FanOutReportProgress, Level, QueueCounts, ReportProgress, Stamper,
+};
+use crate::metrics::{Metric, Metrics};
+use crate::prelude::*;
+use crate::tagged_json::Tagged;
+
+#[derive(Debug, serde::Serialize, Deserialize)]
+pub struct RecordedEvent {
+    #[serde(flatten)]
+    pub meta: EventMeta,
+    /// Tagged the way draw scripts see it, so it replays as is
+    pub event: serde_json::Value,
+}
+
+/// Metric updates aren't events, so they have a time but no sequence number
+#[derive(Debug, serde::Serialize, Deserialize)]
+pub struct RecordedMetric {
+    pub elapsed_ms: f64,
+    pub metric: Metric,
+}
+
+/// A line of a recording; events and metric updates are interleaved in the order they happened
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RecordedLine {
+    Event(RecordedEvent),
+    Metric(RecordedMetric),
+}
+
+/// Borrowed form of `RecordedEvent`, so events can be written without copying them
+#[derive(serde::Serialize)]
+struct RecordedEventRef<'a> {
+    #[serde(flatten)]
+    meta: EventMeta,
+    event: Tagged<'a, dyn Serialize + Send + 'a>,
+}
+
+/// Streams each event as a line of JSON, in the same format as `RecordedEvent`. Events
+/// keep the stamp they were reported with, while metric updates are timed by `stamper`.
+pub struct JsonLinesReportProgress<W: Write + Send> {
+    writer: Mutex<W>,
+    stamper: Stamper,
+}
+
+impl<W: Write + Send> JsonLinesReportProgress<W> {
+    pub fn new(writer: W, stamper: Stamper) -> Self {
+        JsonLinesReportProgress {
+            writer: Mutex::new(writer),
+            stamper,
+        }
+    }
+
+    fn write_line(&self, line: &impl serde::Serialize) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        serde_json::to_writer(&mut *writer, line)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn write_metric(&self, metric: Metric) {
+        let line = RecordedMetric {
+            elapsed_ms: self.stamper.elapsed_ms(),
+            metric,
+        };
+        if let Err(err) = self.write_line(&line) {
+            eprintln!("Couldn't write metric: {err}");
+        }
+    }
+}
+
+impl JsonLinesReportProgress<Box<dyn Write + Send>> {
+    /// Writes to stdout if the path is `-`
+    pub fn create(path: &Path, stamper: Stamper) -> Result<Self> {
+        let writer: Box<dyn Write + Send> = if path == Path::new("-") {
+            Box::new(LineWriter::new(io::stdout()))
+        } else {
+            let file = File::create(path)
+                .map_err(|err| anyhow!("Couldn't create {}: {err}", path.to_string_lossy()))?;
+            Box::new(LineWriter::new(file))
+        };
+        Ok(Self::new(writer, stamper))
+    }
+}
+
+impl<W: Write + Send> ReportProgress for JsonLinesReportProgress<W> {
+    /// Events are meant to come through `StampingReportProgress`; ones that don't are written
+    /// with an empty stamp
+    fn report_progress(&self, data: Box<dyn Serialize + Send>) {
+        self.report_stamped(Level::Info, EventMeta::default(), data)
+    }
+
+    fn report_stamped(&self, _level: Level, meta: EventMeta, data: Box<dyn Serialize + Send>) {
+        let line = RecordedEventRef {
+            meta,
+            event: Tagged(data.as_ref()),
+        };
+        if let Err(err) = self.write_line(&line) {
+            eprintln!("Couldn't write progress event: {err}");
+        }
+    }
+
+    fn report_counter(&self, name: &'static str, delta: i64) {
+        self.write_metric(Metric::Counter {
+            name: name.to_string(),
+            delta,
+        })
+    }
+
+    fn report_gauge(&self, name: &'static str, value: f64) {
+        self.write_metric(Metric::Gauge {
+            name: name.to_string(),
+            value,
+        })
+    }
+
+    fn report_fraction(&self, done: u64, total: u64) {
+        self.write_metric(Metric::Fraction { done, total })
+    }
+}
+
+/// Writes each event to a JSON Lines file, or stdout if the path is `-`, as well as to `inner`
+pub fn record(
+    path: &Path,
+    stamper: Stamper,
+    inner: Box<dyn ReportProgress + Send>,
+) -> Result<FanOutReportProgress> {
+    Ok(FanOutReportProgress {
+        sinks: vec![
+            Box::new(JsonLinesReportProgress::create(path, stamper)?),
+            inner,
+        ],
+    })
+}
+
+impl RecordedEvent {
+    pub fn into_envelope(self) -> Envelope {
+        Envelope {
+            meta: self.meta,
+            event: Box::new(self.event),
+        }
+    }
+}
+
+impl RecordedLine {
+    pub fn elapsed_ms(&self) -> f64 {
+        match self {
+            RecordedLine::Event(it) => it.meta.elapsed_ms,
+            RecordedLine::Metric(it) => it.elapsed_ms,
+        }
+    }
+
+    /// Sends an event on to the window, or applies a metric update; returns false once the
+    /// window is gone
+    pub fn replay(
+        self,
+        sender: &Sender<Envelope>,
+        counts: &QueueCounts,
+        metrics: &Metrics,
+    ) -> bool {
+        match self {
+            RecordedLine::Event(recorded) => counts.send(sender, recorded.into_envelope()),
+            RecordedLine::Metric(recorded) => {
+                metrics.apply(&recorded.metric);
+                true
+            }
+        }
+    }
+}
+
+/// Replays each line once as much time has passed as when it was recorded
+pub fn replay_realtime(
+    recorded: Vec<RecordedLine>,
+    sender: Sender<Envelope>,
+    counts: QueueCounts,
+    metrics: Metrics,
+) {
+    let start = Instant::now();
+    for recorded in recorded {
+        let due = Duration::from_secs_f64(recorded.elapsed_ms().max(0.0) / 1000.0);
+        thread::sleep(due.saturating_sub(start.elapsed()));
+        if !recorded.replay(&sender, &counts, &metrics) {
+            break;
+        }
+    }
+}
+
+pub fn load_recording(path: &Path) -> Result<Vec<RecordedLine>> {
+    let source = fs::read_to_string(path)
+        .map_err(|err| anyhow!("Couldn't read {}: {err}", path.to_string_lossy()))?;
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|err| {
+                anyhow!(
+                    "Invalid event on line {} of {}: {err}",
+                    index + 1,
+                    path.to_string_lossy()
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use rlua::prelude::*;
+
+    use crate::framework::{
+        AsyncReportProgress, ErasingReportProgress, NoOpReportProgress, QueuePolicy,
+        StampingReportProgress,
+    };
+    use crate::lua::draw_runtime::DrawRuntime;
+    use crate::lua::serialize::to_lua;
+    use crate::puzzles;
+    use crate::registry::Puzzle;
+
+    use super::*;
+
+    #[test]
+    fn recording_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("aoc2022-record-{}.jsonl", std::process::id()));
+        let stamper = Stamper::default();
+        stamper.start();
+        let recorder = StampingReportProgress {
+            inner: Box::new(record(&path, stamper.clone(), Box::new(NoOpReportProgress)).unwrap()),
+            stamper,
+        };
+        recorder.report_progress(Box::new(("first", 1)));
+        recorder.report_counter("pairs", 3);
+        recorder.report_progress(Box::new(("second", 2)));
+        drop(recorder);
+
+        let recorded = load_recording(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let (RecordedLine::Event(first), RecordedLine::Metric(metric), RecordedLine::Event(second)) =
+            (&recorded[0], &recorded[1], &recorded[2])
+        else {
+            panic!("Unexpected lines: {recorded:?}");
+        };
+        // metric updates don't take sequence numbers
+        assert_eq!((first.meta.seq, second.meta.seq), (0, 1));
+        assert_eq!(second.event, serde_json::json!(["second", 2]));
+        assert_eq!(
+            metric.metric,
+            Metric::Counter {
+                name: "pairs".to_string(),
+                delta: 3
+            }
+        );
+        assert!(first.meta.elapsed_ms <= metric.elapsed_ms);
+        assert!(metric.elapsed_ms <= second.meta.elapsed_ms);
+        assert_eq!(first.meta.thread, thread::current().name().unwrap());
+    }
+
+    #[test]
+    fn json_lines_trace() {
+        let mut trace = Vec::new();
+        let input =
+            puzzles::day04::parse_input(puzzles::day04::Puzzle::samples()[0].input).unwrap();
+        let sink = JsonLinesReportProgress::new(&mut trace, Stamper::default());
+        puzzles::day04::part_one(&input, &ErasingReportProgress::new(&sink)).unwrap();
+
+        let trace = String::from_utf8(trace).unwrap();
+        let events = trace
+            .lines()
+            .filter_map(|line| match serde_json::from_str(line).unwrap() {
+                RecordedLine::Event(event) => Some(event),
+                RecordedLine::Metric(_) => None,
+            })
+            .collect_vec();
+        assert_eq!(events.len(), 14);
+        assert_eq!(
+            events[0].event,
+            serde_json::json!({
+                "type": "AnalyzePair",
+                "value": [{ "start": 2, "end": 4 }, { "start": 6, "end": 8 }],
+            })
+        );
+    }
+
+    #[test]
+    fn recorded_events_reach_lua_as_typed_variants() {
+        let path =
+            std::env::temp_dir().join(format!("aoc2022-record-lua-{}.jsonl", std::process::id()));
+        let (async_sink, receiver) = AsyncReportProgress::new(QueuePolicy::Block, 100);
+        let stamper = Stamper::default();
+        stamper.start();
+        let recorder = StampingReportProgress {
+            inner: Box::new(record(&path, stamper.clone(), Box::new(async_sink)).unwrap()),
+            stamper,
+        };
+        let input =
+            puzzles::day04::parse_input(puzzles::day04::Puzzle::samples()[0].input).unwrap();
+        puzzles::day04::part_one(&input, &ErasingReportProgress::new(&recorder)).unwrap();
+        drop(recorder);
+        let recorded = load_recording(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // the window and the recording see the same stamp for each event
+        let live = receiver.try_iter().collect_vec();
+        let recorded = recorded
+            .into_iter()
+            .filter_map(|line| match line {
+                RecordedLine::Event(event) => Some(event.meta),
+                RecordedLine::Metric(_) => None,
+            })
+            .collect_vec();
+        assert_eq!(live.len(), 14);
+        assert_eq!(live.len(), recorded.len());
+        for (live, recorded) in live.iter().zip(&recorded) {
+            assert_eq!(live.meta.seq, recorded.seq);
+            assert_eq!(live.meta.thread, recorded.thread);
+            // only as exact as the JSON they went through
+            assert!((live.meta.elapsed_ms - recorded.elapsed_ms).abs() < 1e-9);
+        }
+
+        let envelope = &live[0];
+        Lua::new().context(|ctx| {
+            let LuaValue::Table(event) = to_lua(ctx, &*envelope.event).unwrap() else {
+                panic!("Expected a table");
+            };
+            assert_eq!(event.get::<_, String>("type").unwrap(), "AnalyzePair");
+            let pair: LuaTable = event.get("value").unwrap();
+            let first: LuaTable = pair.get(1).unwrap();
+            assert_eq!(first.get::<_, i64>("start").unwrap(), 2);
+        });
+    }
+
+    #[derive(serde::Serialize)]
+    enum Direction {
+        Left,
+        Right(u32),
+    }
+
+    #[derive(serde::Serialize)]
+    struct Step {
+        direction: Direction,
+    }
+
+    #[test]
+    fn replayed_events_reach_lua_as_they_did_live() {
+        let mut trace = Vec::new();
+        let sink = JsonLinesReportProgress::new(&mut trace, Stamper::default());
+        sink.report_progress(Box::new(Step {
+            direction: Direction::Right(2),
+        }));
+        sink.report_progress(Box::new(Direction::Left));
+        let script_path =
+            std::env::temp_dir().join(format!("aoc2022-replay-{}.lua", std::process::id()));
+        fs::write(
+            &script_path,
+            indoc! {r#"
+                function ProcessEvent(event, meta)
+                  if type(event) == "table" then
+                    assert(event.type == nil, "single field struct read as a variant")
+                    assert(event.direction.type == "Right" and event.direction.value == 2)
+                  else
+                    assert(event == "Left", "unexpected event " .. tostring(event))
+                  end
+                end
+
+                function Draw(ctx) end
+            "#},
+        )
+        .unwrap();
+        let mut runtime = DrawRuntime::new(&script_path);
+        fs::remove_file(&script_path).unwrap();
+
+        for line in String::from_utf8(trace).unwrap().lines() {
+            let RecordedLine::Event(recorded) = serde_json::from_str(line).unwrap() else {
+                panic!("Expected an event");
+            };
+            let envelope = recorded.into_envelope();
+            runtime
+                .handle_event(envelope.event.as_ref(), &envelope.meta)
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use crate::framework::ReportProgress;
+use crate::load_algorithm::Solver;
+use crate::prelude::*;
+use crate::puzzles;
+
+pub type SolveFunc<I> = fn(&I, &Box<dyn ReportProgress>) -> Result<String>;
+
+pub struct Part<I> {
+    pub name: &'static str,
+    pub emits_progress: bool,
+    pub solve: SolveFunc<I>,
+}
+
+/// An example input from the puzzle description, stored next to the puzzle module
+pub struct Sample {
+    pub name: &'static str,
+    pub input: &'static str,
+    /// Expected answers, keyed by part name
+    pub answers: Vec<(&'static str, &'static str)>,
+}
+
+impl Sample {
+    pub fn answer(&self, part: &str) -> Option<&'static str> {
+        self.answers
+            .iter()
+            .find(|(name, _)| *name == part)
+            .map(|(_, answer)| *answer)
+    }
+}
+
+/// Implemented for each puzzle module by `register_puzzle!`
+pub trait Puzzle {
+    type Input: Send + 'static;
+    /// Type of the progress events the parts report, `()` if none do
+    type Event;
+    const TITLE: &'static str;
+    /// Name of `Event`, to find the schema for the puzzle's draw scripts
+    const EVENTS: Option<&'static str>;
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn parts() -> Vec<Part<Self::Input>>;
+    fn samples() -> Vec<Sample>;
+}
+
+/// Declares a puzzle module's title, input parser and parts.
+///
+/// Parts are named after the function that solves them; a part written as
+/// `part_two(report_progress)` is passed a `ReportProgress` for the `events` type and is
+/// marked as emitting progress events. Each sample is read from `<name>.txt` next to the
+/// puzzle module.
+macro_rules! register_puzzle {
+    (
+        title: $title:literal,
+        input: $input:ty,
+        $(events: $events:ty,)?
+        parse: $parse:path,
+        parts: [$($part:ident $(($progress:ident))?),* $(,)?]
+        $(, samples: [
+            $($sample:ident { $($sample_part:ident: $answer:literal),* $(,)? }),* $(,)?
+        ])? $(,)?
+    ) => {
+        pub struct Puzzle;
+
+        impl $crate::registry::Puzzle for Puzzle {
+            type Input = $input;
+            type Event = register_puzzle!(@event $($events)?);
+            const TITLE: &'static str = $title;
+            const EVENTS: Option<&'static str> = register_puzzle!(@events $($events)?);
+
+            fn parse(input: &str) -> $crate::prelude::Result<Self::Input> {
+                $parse(input)
+            }
+
+            fn parts() -> Vec<$crate::registry::Part<Self::Input>> {
+                vec![$(
+                    $crate::registry::Part {
+                        name: stringify!($part),
+                        emits_progress: register_puzzle!(@emits $($progress)?),
+                        solve: |input, report_progress| {
+                            register_puzzle!(@call $part, input, report_progress $(, $progress)?)
+                                .map(|it| it.to_string())
+                        },
+                    }
+                ),*]
+            }
+
+            fn samples() -> Vec<$crate::registry::Sample> {
+                vec![$($(
+                    $crate::registry::Sample {
+                        name: stringify!($sample),
+                        input: include_str!(concat!("./", stringify!($sample), ".txt")),
+                        answers: vec![$((stringify!($sample_part), $answer)),*],
+                    }
+                ),*)?]
+            }
+        }
+    };
+    (@event) => { () };
+    (@event $events:ty) => { $events };
+    (@events) => { None };
+    (@events $events:ty) => { Some(stringify!($events)) };
+    (@emits) => { false };
+    (@emits $progress:ident) => { true };
+    (@call $part:ident, $input:ident, $report_progress:ident) => {{
+        let _ = $report_progress;
+        $part($input)
+    }};
+    (@call $part:ident, $input:ident, $report_progress:ident, $progress:ident) => {
+        $part(
+            $input,
+            &$crate::framework::ErasingReportProgress::<Self::Event>::new($report_progress.as_ref()),
+        )
+    };
+}
+pub(crate) use register_puzzle;
+
+pub struct RegisteredPart {
+    pub name: &'static str,
+    pub emits_progress: bool,
+    pub solver: Solver,
+}
+
+pub struct RegisteredPuzzle {
+    pub day: &'static str,
+    pub title: &'static str,
+    pub events: Option<&'static str>,
+    pub parts: Vec<RegisteredPart>,
+    pub samples: Vec<Sample>,
+}
+
+impl RegisteredPuzzle {
+    pub fn new<P: Puzzle>(day: &'static str) -> Self {
+        RegisteredPuzzle {
+            day,
+            title: P::TITLE,
+            events: P::EVENTS,
+            parts: P::parts()
+                .into_iter()
+                .map(|part| RegisteredPart {
+                    name: part.name,
+                    emits_progress: part.emits_progress,
+                    solver: Solver::new(P::parse, part.solve),
+                })
+                .collect(),
+            samples: P::samples(),
+        }
+    }
+
+    /// Finds a sample by name, or the first one if no name is given
+    pub fn sample(&self, name: Option<&str>) -> Result<&Sample> {
+        match name {
+            Some(name) => self.samples.iter().find(|it| it.name == name),
+            None => self.samples.first(),
+        }
+        .ok_or_else(|| {
+            anyhow!(
+                "Couldn't find sample {} for {}. Available samples: {}",
+                name.unwrap_or("input"),
+                self.day,
+                self.samples.iter().map(|it| it.name).join(", ")
+            )
+        })
+    }
+}
+
+/// Accepts `day07`, `day7`, `07` or `7` for `day07`
+fn normalize_day(day: &str) -> String {
+    let number = day.strip_prefix("day").unwrap_or(day);
+    match number.parse::<u32>() {
+        Ok(number) => format!("day{number:02}"),
+        Err(_) => day.to_string(),
+    }
+}
+
+/// Accepts `part_two`, `part2`, `two` or `2` for `part_two`
+fn normalize_part(part: &str) -> String {
+    let suffix = part
+        .strip_prefix("part")
+        .map(|it| it.trim_start_matches('_'))
+        .unwrap_or(part);
+    match suffix {
+        "1" | "one" => "part_one".to_string(),
+        "2" | "two" => "part_two".to_string(),
+        _ => part.to_string(),
+    }
+}
+
+pub fn lookup(day: &str, part: &str) -> Result<(RegisteredPuzzle, RegisteredPart)> {
+    let (day_name, part_name) = (normalize_day(day), normalize_part(part));
+    let registry = puzzles::registry();
+    let valid_choices = registry
+        .iter()
+        .map(|puzzle| {
+            format!(
+                "  {} {} ({})",
+                puzzle.day,
+                puzzle.parts.iter().map(|it| it.name).join(" | "),
+                puzzle.title
+            )
+        })
+        .join("\n");
+
+    registry
+        .into_iter()
+        .find(|puzzle| puzzle.day == day_name)
+        .and_then(|mut puzzle| {
+            let index = puzzle.parts.iter().position(|it| it.name == part_name)?;
+            let part = puzzle.parts.remove(index);
+            Some((puzzle, part))
+        })
+        .ok_or_else(|| anyhow!("Couldn't find {day} {part}. Valid choices are:\n{valid_choices}"))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::framework::NoOpReportProgress;
+
+    use super::*;
+
+    #[test]
+    fn lookup_aliases() {
+        for (day, part) in [
+            ("day07", "part_two"),
+            ("7", "2"),
+            ("07", "two"),
+            ("day7", "part2"),
+        ] {
+            let (puzzle, part) = lookup(day, part).unwrap();
+            assert_eq!((puzzle.day, part.name), ("day07", "part_two"));
+        }
+    }
+
+    #[test]
+    fn lookup_lists_choices() {
+        let err = lookup("day99", "part_one").err().unwrap();
+        assert!(err
+            .to_string()
+            .contains("day04 part_one | part_two (Camp Cleanup)"));
+    }
+
+    #[test]
+    fn sample_answers() {
+        let report_progress: Box<dyn ReportProgress> = Box::new(NoOpReportProgress);
+        for puzzle in puzzles::registry() {
+            for part in puzzle.parts {
+                let thread_func = part.solver.into_thread_func();
+                for sample in puzzle.samples.iter() {
+                    if let Some(expected) = sample.answer(part.name) {
+                        let actual = thread_func(sample.input, &report_progress).unwrap();
+                        assert_eq!(
+                            actual, expected,
+                            "{} {} {}",
+                            puzzle.day, part.name, sample.name
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
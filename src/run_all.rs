@@ -0,0 +1,126 @@
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use cpu_time::ThreadTime;
+
+use crate::framework::{NoOpReportProgress, ReportProgress};
+use crate::load_algorithm;
+use crate::prelude::*;
+use crate::puzzles;
+use crate::table;
+
+pub struct PuzzleRun {
+    pub day: &'static str,
+    pub part: &'static str,
+    pub result: Result<String>,
+    /// Only present if the puzzle and its input loaded successfully
+    pub wall_time: Option<Duration>,
+    /// CPU time spent by the solver's thread, which isn't skewed by other puzzles running
+    /// alongside it
+    pub cpu_time: Option<Duration>,
+}
+
+pub fn run_puzzle(day: &'static str, part: &'static str, input_path: Option<&Path>) -> PuzzleRun {
+    let loaded = load_algorithm::load_solver(day, part).and_then(|solver| {
+        Ok((
+            solver.into_thread_func(),
+            load_algorithm::load_input(day, input_path)?,
+        ))
+    });
+    let (result, wall_time, cpu_time) = match loaded {
+        Ok((thread_func, input)) => {
+            let report_progress: Box<dyn ReportProgress> = Box::new(NoOpReportProgress);
+            let (start, cpu_start) = (Instant::now(), ThreadTime::now());
+            let result =
+                panic::catch_unwind(AssertUnwindSafe(|| thread_func(&input, &report_progress)))
+                    .unwrap_or_else(|_| Err(anyhow!("Solver panicked")));
+            (result, Some(start.elapsed()), Some(cpu_start.elapsed()))
+        }
+        Err(err) => (Err(err), None, None),
+    };
+    PuzzleRun {
+        day,
+        part,
+        result,
+        wall_time,
+        cpu_time,
+    }
+}
+
+pub fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Runs every puzzle on a pool of `jobs` worker threads, returning runs in registry order
+pub fn run_all(jobs: usize) -> Vec<PuzzleRun> {
+    let tasks = puzzles::registry()
+        .iter()
+        .flat_map(|puzzle| puzzle.parts.iter().map(|part| (puzzle.day, part.name)))
+        .collect_vec();
+    let next_task = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        let workers = (0..jobs.clamp(1, tasks.len().max(1)))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut runs = vec![];
+                    loop {
+                        let index = next_task.fetch_add(1, Ordering::Relaxed);
+                        let Some(&(day, part)) = tasks.get(index) else {
+                            break runs;
+                        };
+                        runs.push((index, run_puzzle(day, part, None)));
+                    }
+                })
+            })
+            .collect_vec();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect_vec()
+    })
+    .into_iter()
+    .sorted_by_key(|(index, _)| *index)
+    .map(|(_, run)| run)
+    .collect()
+}
+
+pub fn print_table(runs: &[PuzzleRun]) {
+    let format_time = |time: Option<Duration>| {
+        time.map(|it| format!("{it:.2?}"))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let rows = runs
+        .iter()
+        .map(|run| {
+            let (answer, error) = match &run.result {
+                Ok(answer) => (answer.to_owned(), String::new()),
+                Err(err) => (String::new(), err.to_string()),
+            };
+            [
+                run.day.to_string(),
+                run.part.to_string(),
+                answer,
+                format_time(run.wall_time),
+                format_time(run.cpu_time),
+                error,
+            ]
+        })
+        .collect_vec();
+    table::print_table(["Day", "Part", "Answer", "Wall", "CPU", "Error"], &rows);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parallel_runs_keep_registry_order() {
+        let order = |runs: Vec<PuzzleRun>| runs.iter().map(|it| (it.day, it.part)).collect_vec();
+        assert_eq!(order(run_all(4)), order(run_all(1)));
+    }
+}
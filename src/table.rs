@@ -0,0 +1,31 @@
+use crate::prelude::*;
+
+pub fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let widths = (0..N)
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain(std::iter::once(header[column].chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect_vec();
+
+    let format_row = |row: &[&str]| {
+        row.iter()
+            .zip(widths.iter())
+            .map(|(cell, &width)| format!("{cell:width$}"))
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(&header));
+    println!(
+        "{}",
+        widths.iter().map(|&width| "-".repeat(width)).join("-+-")
+    );
+    for row in rows.iter() {
+        println!("{}", format_row(&row.each_ref().map(|it| it.as_str())));
+    }
+}
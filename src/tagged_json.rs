@@ -0,0 +1,378 @@
+use serde::ser::{self, Serialize};
+use serde_json::{Map, Value};
+
+/// Serializes its contents to JSON in the shape the Lua serializer gives draw scripts: enum
+/// variants become `{"type": ..., "value": ...}` objects at any depth, and unit variants plain
+/// strings. Recordings store events this way so that replaying them needs no guesswork.
+pub struct Tagged<'a, T: ?Sized>(pub &'a T);
+
+impl<T: ?Sized + Serialize> Serialize for Tagged<'_, T> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0
+            .serialize(TaggedSerializer)
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+fn tagged(variant: &str, value: Value) -> Value {
+    let mut object = Map::new();
+    object.insert("type".to_string(), Value::from(variant));
+    object.insert("value".to_string(), value);
+    Value::Object(object)
+}
+
+struct TaggedSerializer;
+
+impl ser::Serializer for TaggedSerializer {
+    type Ok = Value;
+    type Error = serde_json::Error;
+    type SerializeSeq = TaggedArray;
+    type SerializeTuple = TaggedArray;
+    type SerializeTupleStruct = TaggedArray;
+    type SerializeTupleVariant = TaggedVariant<TaggedArray>;
+    type SerializeMap = TaggedObject;
+    type SerializeStruct = TaggedObject;
+    type SerializeStructVariant = TaggedVariant<TaggedObject>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Self::Error> {
+        Ok(Value::from(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_none(self) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Self::Error> {
+        Ok(Value::from(variant))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        Ok(tagged(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<TaggedArray, Self::Error> {
+        Ok(TaggedArray(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<TaggedArray, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<TaggedArray, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<TaggedVariant<TaggedArray>, Self::Error> {
+        Ok(TaggedVariant {
+            variant,
+            fields: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<TaggedObject, Self::Error> {
+        Ok(TaggedObject {
+            object: Map::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<TaggedObject, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<TaggedVariant<TaggedObject>, Self::Error> {
+        Ok(TaggedVariant {
+            variant,
+            fields: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+struct TaggedArray(Vec<Value>);
+
+impl ser::SerializeSeq for TaggedArray {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.push(value.serialize(TaggedSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Array(self.0))
+    }
+}
+
+impl ser::SerializeTuple for TaggedArray {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for TaggedArray {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct TaggedObject {
+    object: Map<String, Value>,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for TaggedObject {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    /// JSON keys are strings, so numbers and bools used as keys are written as text, the way
+    /// serde_json writes them
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.key = Some(match key.serialize(TaggedSerializer)? {
+            Value::String(key) => key,
+            key @ (Value::Number(_) | Value::Bool(_)) => key.to_string(),
+            _ => return Err(ser::Error::custom("map keys must be strings or numbers")),
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("map value without a key"))?;
+        self.object.insert(key, value.serialize(TaggedSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Object(self.object))
+    }
+}
+
+impl ser::SerializeStruct for TaggedObject {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.object
+            .insert(key.to_string(), value.serialize(TaggedSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// The fields of a tuple or struct variant, which end up under `value`
+struct TaggedVariant<F> {
+    variant: &'static str,
+    fields: F,
+}
+
+impl ser::SerializeTupleVariant for TaggedVariant<TaggedArray> {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(&mut self.fields, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(tagged(self.variant, ser::SerializeSeq::end(self.fields)?))
+    }
+}
+
+impl ser::SerializeStructVariant for TaggedVariant<TaggedObject> {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        ser::SerializeStruct::serialize_field(&mut self.fields, key, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(tagged(self.variant, ser::SerializeMap::end(self.fields)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Serialize;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    enum Direction {
+        Left,
+        Right(u32),
+    }
+
+    #[derive(Serialize)]
+    struct Step {
+        direction: Direction,
+    }
+
+    #[derive(Serialize)]
+    enum Progress {
+        Moved(Step, Direction),
+        Found { position: Option<i64> },
+    }
+
+    #[test]
+    fn tags_variants_at_any_depth() {
+        let event = Progress::Moved(
+            Step {
+                direction: Direction::Right(2),
+            },
+            Direction::Left,
+        );
+        assert_eq!(
+            serde_json::to_value(Tagged(&event)).unwrap(),
+            json!({
+                "type": "Moved",
+                "value": [{ "direction": { "type": "Right", "value": 2 } }, "Left"],
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(Tagged(&Progress::Found { position: None })).unwrap(),
+            json!({ "type": "Found", "value": { "position": null } })
+        );
+        // a struct with a single field stays a struct
+        assert_eq!(
+            serde_json::to_value(Tagged(&Step {
+                direction: Direction::Left
+            }))
+            .unwrap(),
+            json!({ "direction": "Left" })
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use ggez::glam::Vec2;
+use ggez::graphics::{self, Canvas, Color, DrawMode, DrawParam, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+
+use crate::draw_utils;
+use crate::framework::Event;
+use crate::variant_name::variant_name;
+
+const BAR_HEIGHT: f32 = 16.0;
+const LEGEND_HEIGHT: f32 = 20.0;
+/// Events are counted in at most this many runs, which get longer as events come in
+const MAX_BUCKETS: usize = 512;
+const PALETTE: [u32; 8] = [
+    0x2196F3, 0xFF9800, 0x9C27B0, 0x4CAF50, 0xF44336, 0x00BCD4, 0x795548, 0xE91E63,
+];
+
+/// A bar along the bottom of the window with a colored mark for each progress event
+#[derive(Default)]
+pub struct Timeline {
+    /// Names of the event variants seen so far, in order of first appearance
+    kinds: Vec<String>,
+    kind_counts: Vec<usize>,
+    event_kinds: Vec<usize>,
+    /// Count of each kind in consecutive runs of `1 << bucket_shift` events, kept up to date
+    /// as events come in so drawing doesn't have to go through all of them
+    buckets: Vec<Vec<usize>>,
+    bucket_shift: u32,
+    dragging: bool,
+    /// Where each legend entry was last drawn, for click handling
+    legend: Vec<(usize, Rect)>,
+}
+
+fn kind_color(kind: usize) -> Color {
+    Color::from_rgb_u32(PALETTE[kind % PALETTE.len()])
+}
+
+fn bar_rect(ctx: &Context) -> Rect {
+    let (width, height) = ctx.gfx.drawable_size();
+    Rect::new(0.0, height - BAR_HEIGHT, width, BAR_HEIGHT)
+}
+
+impl Timeline {
+    /// Height of the area the timeline covers at the bottom of the window
+    pub const HEIGHT: f32 = BAR_HEIGHT + LEGEND_HEIGHT;
+
+    pub fn push(&mut self, event: &Event) {
+        let name = variant_name(event);
+        let kind = match self.kinds.iter().position(|it| *it == name) {
+            Some(kind) => kind,
+            None => {
+                self.kinds.push(name.into_owned());
+                self.kind_counts.push(0);
+                self.kinds.len() - 1
+            }
+        };
+        self.kind_counts[kind] += 1;
+
+        let mut bucket = self.event_kinds.len() >> self.bucket_shift;
+        if bucket == MAX_BUCKETS {
+            // merge pairs of buckets to make room
+            self.buckets = self
+                .buckets
+                .chunks(2)
+                .map(|pair| {
+                    let mut merged = pair[0].clone();
+                    for counts in &pair[1..] {
+                        merged.resize(merged.len().max(counts.len()), 0);
+                        for (total, count) in merged.iter_mut().zip(counts) {
+                            *total += count;
+                        }
+                    }
+                    merged
+                })
+                .collect();
+            self.bucket_shift += 1;
+            bucket = self.event_kinds.len() >> self.bucket_shift;
+        }
+        if bucket == self.buckets.len() {
+            self.buckets.push(Vec::new());
+        }
+        let counts = &mut self.buckets[bucket];
+        if counts.len() <= kind {
+            counts.resize(kind + 1, 0);
+        }
+        counts[kind] += 1;
+        self.event_kinds.push(kind);
+    }
+
+    /// Variant name of the event at `index`
+    pub fn kind(&self, index: usize) -> &str {
+        &self.kinds[self.event_kinds[index]]
+    }
+
+    fn position_at(&self, ctx: &Context, x: f32) -> usize {
+        let bar = bar_rect(ctx);
+        let fraction = ((x - bar.x) / bar.w).clamp(0.0, 1.0);
+        (fraction * self.event_kinds.len() as f32).round() as usize
+    }
+
+    /// Returns the position to seek to, if the click landed on the timeline
+    pub fn mouse_down(&mut self, ctx: &Context, x: f32, y: f32, position: usize) -> Option<usize> {
+        if bar_rect(ctx).contains([x, y]) {
+            self.dragging = true;
+            return Some(self.position_at(ctx, x));
+        }
+        // clicking a legend entry jumps just past the next event of that kind
+        let (kind, _) = self.legend.iter().find(|(_, rect)| rect.contains([x, y]))?;
+        self.event_kinds
+            .iter()
+            .enumerate()
+            .skip(position)
+            .chain(self.event_kinds.iter().enumerate())
+            .find(|(_, it)| *it == kind)
+            .map(|(index, _)| index + 1)
+    }
+
+    pub fn mouse_moved(&self, ctx: &Context, x: f32) -> Option<usize> {
+        self.dragging.then(|| self.position_at(ctx, x))
+    }
+
+    pub fn mouse_up(&mut self) {
+        self.dragging = false;
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas, position: usize) -> GameResult {
+        let bar = bar_rect(ctx);
+        let mut mesh = MeshBuilder::new();
+        mesh.rectangle(DrawMode::fill(), bar, Color::from_rgb(0xEE, 0xEE, 0xEE))?;
+
+        // each bucket shows its rarest kind so that unusual moments stay visible
+        let count = self.event_kinds.len();
+        let bucket_width = bar.w * (1 << self.bucket_shift) as f32 / count.max(1) as f32;
+        for (bucket, counts) in self.buckets.iter().enumerate() {
+            let rarest = counts
+                .iter()
+                .enumerate()
+                .filter(|(_, &it)| it > 0)
+                .min_by_key(|(kind, _)| self.kind_counts[*kind]);
+            if let Some((kind, _)) = rarest {
+                let x = bar.x + bucket as f32 * bucket_width;
+                mesh.rectangle(
+                    DrawMode::fill(),
+                    Rect::new(x, bar.y + 3.0, bucket_width.max(1.0), bar.h - 6.0),
+                    kind_color(kind),
+                )?;
+            }
+        }
+
+        let cursor_x = bar.x + bar.w * position as f32 / count.max(1) as f32;
+        mesh.rectangle(
+            DrawMode::fill(),
+            Rect::new(cursor_x - 1.0, bar.y, 2.0, bar.h),
+            draw_utils::BLACK,
+        )?;
+        canvas.draw(
+            &graphics::Mesh::from_data(ctx, mesh.build()),
+            DrawParam::default(),
+        );
+
+        self.legend.clear();
+        let mut x = bar.x + 8.0;
+        let y = bar.y - LEGEND_HEIGHT + 2.0;
+        for (kind, name) in self.kinds.iter().enumerate() {
+            let mut text = graphics::Text::new(format!("{name} ({})", self.kind_counts[kind]));
+            text.set_scale(14.0);
+            let size = text.measure(ctx)?;
+            canvas.draw(
+                &text,
+                DrawParam::default()
+                    .dest(Vec2::new(x, y))
+                    .color(kind_color(kind)),
+            );
+            self.legend.push((kind, Rect::new(x, y, size.x, size.y)));
+            x += size.x + 12.0;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Serialize;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    enum Progress {
+        Started,
+        AnalyzePair(u32, u32),
+        IntersectionFound { position: i64 },
+    }
+
+    #[test]
+    fn groups_events_by_variant() {
+        let mut timeline = Timeline::default();
+        for event in [
+            Progress::Started,
+            Progress::AnalyzePair(1, 2),
+            Progress::IntersectionFound { position: 4 },
+            Progress::AnalyzePair(3, 4),
+        ] {
+            timeline.push(&event);
+        }
+        timeline.push(&42);
+        assert_eq!(
+            timeline.kinds,
+            ["Started", "AnalyzePair", "IntersectionFound", "event"]
+        );
+        assert_eq!(timeline.event_kinds, [0, 1, 2, 1, 3]);
+        assert_eq!(timeline.kind_counts, [1, 2, 1, 1]);
+        assert_eq!(
+            timeline.buckets,
+            [
+                vec![1],
+                vec![0, 1],
+                vec![0, 0, 1],
+                vec![0, 1],
+                vec![0, 0, 0, 1]
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_buckets_as_events_come_in() {
+        let mut timeline = Timeline::default();
+        for index in 0..MAX_BUCKETS * 2 + 1 {
+            if index % 4 == 0 {
+                timeline.push(&Progress::Started);
+            } else {
+                timeline.push(&Progress::AnalyzePair(1, 2));
+            }
+        }
+        assert_eq!(timeline.bucket_shift, 2);
+        assert_eq!(timeline.buckets.len(), MAX_BUCKETS / 2 + 1);
+        assert_eq!(timeline.buckets[0], [1, 3]);
+        assert_eq!(timeline.buckets[MAX_BUCKETS / 2], [1]);
+        let total: usize = timeline.buckets.iter().flatten().sum();
+        assert_eq!(total, timeline.event_kinds.len());
+    }
+}
@@ -0,0 +1,315 @@
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+
+use serde::ser::{self, Impossible, Serialize, SerializeMap};
+
+use crate::framework::Event;
+
+/// Names an event after its variant, for enums serialized the default (externally tagged)
+/// way, or after the `type` of replayed events, which are already tagged the way Lua sees
+/// them. Only gets as far into the event as it needs to, so naming a typed event doesn't
+/// allocate.
+pub fn variant_name(event: &Event) -> Cow<'static, str> {
+    let mut name = None;
+    match erased_serde::serialize(event, VariantName(&mut name)) {
+        Ok(()) => name.unwrap_or(Cow::Borrowed("event")),
+        Err(_) => Cow::Borrowed("event"),
+    }
+}
+
+#[derive(Debug)]
+struct NotAVariant;
+
+impl Display for NotAVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("not an enum variant")
+    }
+}
+
+impl std::error::Error for NotAVariant {}
+
+impl ser::Error for NotAVariant {
+    fn custom<T: Display>(_msg: T) -> Self {
+        NotAVariant
+    }
+}
+
+/// Stores the name it finds, as the `Ok` value has to go through erased-serde
+struct VariantName<'a>(&'a mut Option<Cow<'static, str>>);
+
+/// The fields of a variant, which aren't needed for its name
+struct SkipFields;
+
+impl ser::SerializeTupleVariant for SkipFields {
+    type Ok = ();
+    type Error = NotAVariant;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _value: &T) -> Result<(), NotAVariant> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), NotAVariant> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for SkipFields {
+    type Ok = ();
+    type Error = NotAVariant;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        _value: &T,
+    ) -> Result<(), NotAVariant> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), NotAVariant> {
+        Ok(())
+    }
+}
+
+/// Looks for a `type` entry in a map
+struct TypeEntry<'a> {
+    name: &'a mut Option<Cow<'static, str>>,
+    next_is_type: bool,
+}
+
+impl SerializeMap for TypeEntry<'_> {
+    type Ok = ();
+    type Error = NotAVariant;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), NotAVariant> {
+        let mut key_name = None;
+        self.next_is_type = key.serialize(VariantName(&mut key_name)).is_ok()
+            && key_name.as_deref() == Some("type");
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), NotAVariant> {
+        if self.next_is_type {
+            value.serialize(VariantName(self.name))?;
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), NotAVariant> {
+        match self.name {
+            Some(_) => Ok(()),
+            None => Err(NotAVariant),
+        }
+    }
+}
+
+impl<'a> ser::Serializer for VariantName<'a> {
+    type Ok = ();
+    type Error = NotAVariant;
+    type SerializeSeq = Impossible<(), NotAVariant>;
+    type SerializeTuple = Impossible<(), NotAVariant>;
+    type SerializeTupleStruct = Impossible<(), NotAVariant>;
+    type SerializeTupleVariant = SkipFields;
+    type SerializeMap = TypeEntry<'a>;
+    type SerializeStruct = Impossible<(), NotAVariant>;
+    type SerializeStructVariant = SkipFields;
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), NotAVariant> {
+        *self.0 = Some(Cow::Borrowed(variant));
+        Ok(())
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _value: &T,
+    ) -> Result<(), NotAVariant> {
+        *self.0 = Some(Cow::Borrowed(variant));
+        Ok(())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SkipFields, NotAVariant> {
+        *self.0 = Some(Cow::Borrowed(variant));
+        Ok(SkipFields)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SkipFields, NotAVariant> {
+        *self.0 = Some(Cow::Borrowed(variant));
+        Ok(SkipFields)
+    }
+
+    /// Unit variants that have been through JSON, as in recordings, are plain strings
+    fn serialize_str(self, v: &str) -> Result<(), NotAVariant> {
+        *self.0 = Some(Cow::Owned(v.to_string()));
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), NotAVariant> {
+        value.serialize(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<TypeEntry<'a>, NotAVariant> {
+        Ok(TypeEntry {
+            name: self.0,
+            next_is_type: false,
+        })
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_char(self, _v: char) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_none(self) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_unit(self) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, NotAVariant> {
+        Err(NotAVariant)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, NotAVariant> {
+        Err(NotAVariant)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Serialize;
+
+    use crate::alloc_profile::measure;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    enum Progress {
+        Started,
+        AnalyzePair(u32, u32),
+        IntersectionFound { position: i64 },
+        Found(u32),
+    }
+
+    #[test]
+    fn names_variants_and_replayed_events() {
+        assert_eq!(variant_name(&Progress::Started), "Started");
+        assert_eq!(variant_name(&Progress::AnalyzePair(1, 2)), "AnalyzePair");
+        assert_eq!(
+            variant_name(&Progress::IntersectionFound { position: 4 }),
+            "IntersectionFound"
+        );
+        assert!(matches!(
+            variant_name(&Progress::Found(3)),
+            Cow::Borrowed("Found")
+        ));
+        let replayed = serde_json::json!({ "type": "AnalyzePair", "value": [1, 2] });
+        assert_eq!(variant_name(&replayed), "AnalyzePair");
+        assert_eq!(variant_name(&"Started"), "Started");
+        assert_eq!(variant_name(&42), "event");
+        assert_eq!(variant_name(&serde_json::json!({ "value": 1 })), "event");
+    }
+
+    #[test]
+    fn naming_typed_events_doesnt_allocate() {
+        let event = Progress::IntersectionFound { position: 4 };
+        let (name, stats) = measure(|| variant_name(&event));
+        assert_eq!(name, "IntersectionFound");
+        assert_eq!(stats.allocations, 0);
+    }
+}
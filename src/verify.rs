@@ -0,0 +1,337 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::load_algorithm;
+use crate::prelude::*;
+use crate::puzzles;
+use crate::run_all;
+use crate::table;
+
+/// Known answers, keyed by day, then part, then input name
+pub type Answers = BTreeMap<String, BTreeMap<String, BTreeMap<String, String>>>;
+
+pub enum Status {
+    Pass,
+    Fail {
+        expected: String,
+    },
+    Missing,
+    /// The input file for this answer isn't present in this checkout
+    Skipped,
+    Error(Error),
+}
+
+pub struct Check {
+    pub day: &'static str,
+    pub part: &'static str,
+    pub input_name: String,
+    pub actual: Option<String>,
+    pub status: Status,
+}
+
+pub fn load_answers(path: &Path) -> Result<Answers> {
+    if !path.exists() {
+        return Ok(Answers::new());
+    }
+    let source = fs::read_to_string(path)?;
+    toml::from_str(&source)
+        .map_err(|err| anyhow!("Couldn't parse {}: {err}", path.to_string_lossy()))
+}
+
+/// One table per part with a blank line between each; toml on its own only spaces out the
+/// tables that share a day
+fn format_answers(answers: &Answers) -> Result<String> {
+    let tables = answers
+        .iter()
+        .flat_map(|(day, parts)| parts.iter().map(move |(part, inputs)| (day, part, inputs)))
+        .map(|(day, part, inputs)| {
+            toml::to_string(&BTreeMap::from([(day, BTreeMap::from([(part, inputs)]))]))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(tables.join("\n"))
+}
+
+pub fn save_answers(path: &Path, answers: &Answers) -> Result<()> {
+    fs::write(path, format_answers(answers)?)
+        .map_err(|err| anyhow!("Couldn't write {}: {err}", path.to_string_lossy()))
+}
+
+/// Fails on days or parts that aren't registered, which would otherwise never be checked
+pub fn check_known(answers: &Answers) -> Result<()> {
+    let registry = puzzles::registry();
+    for (day, parts) in answers {
+        let puzzle = registry
+            .iter()
+            .find(|it| it.day == day)
+            .ok_or_else(|| anyhow!("Unknown day {day} in answers"))?;
+        for part in parts.keys() {
+            if !puzzle.parts.iter().any(|it| it.name == part) {
+                bail!("Unknown part {day} {part} in answers");
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn verify(answers: &Answers) -> Vec<Check> {
+    puzzles::registry()
+        .iter()
+        .flat_map(|puzzle| puzzle.parts.iter().map(|part| (puzzle.day, part.name)))
+        .flat_map(|(day, part)| {
+            let expected = answers.get(day).and_then(|it| it.get(part));
+            let mut input_names: BTreeSet<String> = expected
+                .map(|it| it.keys().cloned().collect())
+                .unwrap_or_default();
+            if load_algorithm::named_input_path(day).exists() {
+                input_names.insert(day.to_string());
+            }
+
+            input_names.into_iter().map(move |input_name| {
+                let input_path = load_algorithm::named_input_path(&input_name);
+                if !input_path.exists() {
+                    return Check {
+                        day,
+                        part,
+                        input_name,
+                        actual: None,
+                        status: Status::Skipped,
+                    };
+                }
+                let run = run_all::run_puzzle(day, part, Some(&input_path));
+                let expected = expected.and_then(|it| it.get(&input_name));
+                let (actual, status) = match run.result {
+                    Ok(actual) => {
+                        let status = match expected {
+                            Some(expected) if *expected == actual => Status::Pass,
+                            Some(expected) => Status::Fail {
+                                expected: expected.to_owned(),
+                            },
+                            None => Status::Missing,
+                        };
+                        (Some(actual), status)
+                    }
+                    Err(err) => (None, Status::Error(err)),
+                };
+                Check {
+                    day,
+                    part,
+                    input_name,
+                    actual,
+                    status,
+                }
+            })
+        })
+        .collect()
+}
+
+pub fn print_checks(checks: &[Check]) {
+    let rows = checks
+        .iter()
+        .map(|check| {
+            let (status, details) = match &check.status {
+                Status::Pass => ("pass", check.actual.clone().unwrap_or_default()),
+                Status::Fail { .. } => ("FAIL", check.actual.clone().unwrap_or_default()),
+                Status::Missing => ("missing", check.actual.clone().unwrap_or_default()),
+                Status::Skipped => (
+                    "skipped",
+                    format!(
+                        "no input at {}",
+                        load_algorithm::named_input_path(&check.input_name).to_string_lossy()
+                    ),
+                ),
+                Status::Error(err) => ("ERROR", err.to_string()),
+            };
+            [
+                check.day.to_string(),
+                check.part.to_string(),
+                check.input_name.clone(),
+                status.to_string(),
+                details,
+            ]
+        })
+        .collect_vec();
+    table::print_table(["Day", "Part", "Input", "Status", "Answer"], &rows);
+
+    for check in checks {
+        if let Status::Fail { expected } = &check.status {
+            println!();
+            println!("{} {} ({}):", check.day, check.part, check.input_name);
+            for line in expected.lines() {
+                println!("- {line}");
+            }
+            for line in check.actual.as_deref().unwrap_or_default().lines() {
+                println!("+ {line}");
+            }
+        }
+    }
+}
+
+/// Returns false if any answer was wrong or any puzzle failed to run
+pub fn run(answers_path: &Path) -> Result<bool> {
+    let mut answers = load_answers(answers_path)?;
+    check_known(&answers)?;
+    let checks = verify(&answers);
+    print_checks(&checks);
+
+    let count =
+        |predicate: fn(&Status) -> bool| checks.iter().filter(|it| predicate(&it.status)).count();
+    let passed = count(|it| matches!(it, Status::Pass));
+    let failed = count(|it| matches!(it, Status::Fail { .. }));
+    let missing = count(|it| matches!(it, Status::Missing));
+    let skipped = count(|it| matches!(it, Status::Skipped));
+    let errors = count(|it| matches!(it, Status::Error(_)));
+    println!();
+    println!(
+        "{passed} passed, {failed} failed, {missing} missing, {skipped} skipped, {errors} errors"
+    );
+
+    if failed > 0 || errors > 0 {
+        return Ok(false);
+    }
+
+    if missing > 0 {
+        print!(
+            "Record {missing} missing answers in {}? [y/N] ",
+            answers_path.to_string_lossy()
+        );
+        io::stdout().flush()?;
+        let mut reply = String::new();
+        io::stdin().read_line(&mut reply)?;
+        if reply.trim().eq_ignore_ascii_case("y") {
+            for check in checks.iter() {
+                if let (Status::Missing, Some(actual)) = (&check.status, &check.actual) {
+                    answers
+                        .entry(check.day.to_string())
+                        .or_default()
+                        .entry(check.part.to_string())
+                        .or_default()
+                        .insert(check.input_name.clone(), actual.to_owned());
+                }
+            }
+            save_answers(answers_path, &answers)?;
+            println!("Recorded!");
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use indoc::indoc;
+
+    use super::*;
+
+    fn answers(source: &str) -> Answers {
+        toml::from_str(source).unwrap()
+    }
+
+    fn test_algo_checks(answers: &Answers) -> Vec<Check> {
+        verify(answers)
+            .into_iter()
+            .filter(|it| it.day == "test_algo")
+            .collect()
+    }
+
+    fn answers_file(name: &str, source: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "aoc2022-answers-{name}-{}.toml",
+            std::process::id()
+        ));
+        fs::write(&path, source).unwrap();
+        path
+    }
+
+    #[test]
+    fn matching_answers_pass() {
+        let answers = answers(indoc! {r#"
+            [test_algo.part_one]
+            test_algo = "3394106"
+            [test_algo.part_two]
+            test_algo = "5088280"
+        "#});
+        let checks = test_algo_checks(&answers);
+        assert_eq!(checks.len(), 2);
+        assert!(checks.iter().all(|it| matches!(it.status, Status::Pass)));
+    }
+
+    #[test]
+    fn mismatching_answer_fails_the_run() {
+        let source = indoc! {r#"
+            [test_algo.part_one]
+            test_algo = "3394106"
+            [test_algo.part_two]
+            test_algo = "42"
+        "#};
+        let checks = test_algo_checks(&answers(source));
+        assert!(matches!(checks[0].status, Status::Pass));
+        assert!(matches!(
+            &checks[1].status,
+            Status::Fail { expected } if expected == "42"
+        ));
+        assert_eq!(checks[1].actual.as_deref(), Some("5088280"));
+
+        let path = answers_file("mismatch", source);
+        let result = run(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(!result.unwrap());
+    }
+
+    #[test]
+    fn missing_answer_is_reported() {
+        let answers = answers(indoc! {r#"
+            [test_algo.part_one]
+            test_algo = "3394106"
+        "#});
+        let checks = test_algo_checks(&answers);
+        assert!(matches!(checks[0].status, Status::Pass));
+        assert!(matches!(checks[1].status, Status::Missing));
+        assert_eq!(checks[1].actual.as_deref(), Some("5088280"));
+    }
+
+    #[test]
+    fn unknown_day_or_part_is_an_error() {
+        let unknown_day = answers(indoc! {r#"
+            [day99.part_one]
+            day99 = "1"
+        "#});
+        assert!(check_known(&unknown_day).is_err());
+        let unknown_part = answers(indoc! {r#"
+            [test_algo.part_three]
+            test_algo = "1"
+        "#});
+        assert!(check_known(&unknown_part).is_err());
+
+        let path = answers_file("unknown", "[day99.part_one]\nday99 = \"1\"\n");
+        let result = run(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn answers_file_covers_every_part() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("answers.toml");
+        let answers = load_answers(&path).unwrap();
+        for puzzle in puzzles::registry() {
+            for part in puzzle.parts.iter() {
+                assert!(
+                    answers
+                        .get(puzzle.day)
+                        .is_some_and(|it| it.contains_key(part.name)),
+                    "answers.toml has no table for {} {}",
+                    puzzle.day,
+                    part.name
+                );
+            }
+        }
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format_answers(&answers).unwrap(),
+            "answers.toml isn't in the form save_answers writes"
+        );
+    }
+}